@@ -87,6 +87,7 @@ pub fn execute(non_interactive: bool) -> anyhow::Result<()> {
         python_version,
         dependencies: HashMap::new(),
         virtual_environment,
+        ..Default::default()
     };
 
     let json = serde_json::to_string_pretty(&config)?;
@@ -1,16 +1,182 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
 use crate::cli::progress::CliProgressReporter;
 use crate::cli::ux;
 use crate::core::cache::Cache;
-use crate::core::config;
+use crate::core::config::{self, Config, PrereleaseMode, ResolutionStrategy};
+use crate::core::error::WovenError;
 use crate::core::installer;
-use crate::core::lock::Lockfile;
+use crate::core::lock::{Lockfile, Selection};
+use crate::core::resolver::ResolveOptions;
+use crate::core::timestamp::Timestamp;
+use crate::dependencies::index::Index;
 
-pub async fn execute(force_resolve: bool) -> anyhow::Result<()> {
-    let config = config::read_config("wovenpkg.json")?;
+/// Resolution settings given on the command line. They take precedence over
+/// `wovenpkg.json` for a single run and are never written back to it.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct ResolveArgs {
+    /// Pre-release policy for this run
+    #[arg(long, value_enum)]
+    pub prerelease: Option<PrereleaseMode>,
+    /// Prefer the highest or lowest allowed versions for this run
+    #[arg(long, value_enum)]
+    pub resolution: Option<ResolutionStrategy>,
+    /// Ignore files uploaded after this RFC 3339 timestamp, e.g. 2026-01-01T00:00:00Z
+    #[arg(long, value_name = "TIMESTAMP")]
+    pub exclude_newer: Option<Timestamp>,
+    /// Lock for every platform, keeping environment markers in wovenpkg.lock
+    #[arg(long)]
+    pub universal: bool,
+    /// Package index (PEP 503 simple API) for this run
+    #[arg(long, value_name = "URL")]
+    pub index_url: Option<String>,
+    /// Wheelhouse directory or flat HTML page searched before the indexes (repeatable)
+    #[arg(long, value_name = "PATH_OR_URL")]
+    pub find_links: Vec<String>,
+}
+
+impl ResolveArgs {
+    /// Whether any flag was given, in which case the lockfile cannot be reused.
+    pub const fn is_set(&self) -> bool {
+        self.prerelease.is_some()
+            || self.resolution.is_some()
+            || self.exclude_newer.is_some()
+            || self.universal
+            || self.index_url.is_some()
+            || !self.find_links.is_empty()
+    }
+
+    /// Overrides the matching `config` fields with the flags that were given.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(mode) = self.prerelease {
+            config.prerelease = mode;
+        }
+        if let Some(strategy) = self.resolution {
+            config.resolution = strategy;
+        }
+        if let Some(cutoff) = &self.exclude_newer {
+            config.exclude_newer = Some(cutoff.clone());
+        }
+        if self.universal {
+            config.universal = true;
+        }
+        if let Some(url) = &self.index_url {
+            config.index_url = Some(url.clone());
+        }
+        config.find_links.extend(self.find_links.iter().cloned());
+    }
+
+    /// Resolver options for `config` with these flags applied.
+    pub fn options(&self, config: &Config) -> Result<ResolveOptions, WovenError> {
+        let mut options = ResolveOptions::from_config(config)?;
+        if let Some(mode) = self.prerelease {
+            options.prerelease = mode;
+        }
+        if let Some(strategy) = self.resolution {
+            options.resolution = strategy;
+        }
+        if let Some(cutoff) = &self.exclude_newer {
+            options.exclude_newer = Some(cutoff.clone());
+        }
+        options.universal |= self.universal;
+        if let Some(url) = &self.index_url {
+            options.indexes.default = Index::new(url);
+        }
+        options.indexes.find_links.extend(self.find_links.iter().cloned());
+        Ok(options)
+    }
+}
+
+/// Dependency group installed unless `--no-group dev` or `--only-group` is given.
+pub const DEFAULT_GROUP: &str = "dev";
+
+/// Which dependency groups and project extras an install puts into the
+/// environment. Everything is always locked, so choosing never changes the lockfile.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct SelectionArgs {
+    /// Also install this dependency group; with `add`, the group to add to
+    #[arg(long = "group", value_name = "GROUP")]
+    pub groups: Vec<String>,
+    /// Leave this dependency group out, including the default `dev` group
+    #[arg(long = "no-group", value_name = "GROUP")]
+    pub no_groups: Vec<String>,
+    /// Install only this dependency group, without the project's dependencies
+    #[arg(
+        long = "only-group",
+        value_name = "GROUP",
+        conflicts_with_all = ["groups", "no_groups", "extras", "all_extras"]
+    )]
+    pub only_groups: Vec<String>,
+    /// Also install this extra of the project; with `add`, the extra to add to
+    #[arg(long = "extra", value_name = "EXTRA")]
+    pub extras: Vec<String>,
+    /// Install every extra of the project
+    #[arg(long, conflicts_with = "extras")]
+    pub all_extras: bool,
+}
+
+impl SelectionArgs {
+    /// The part of the lock to install for `config`'s groups and extras.
+    pub fn selection(&self, config: &Config) -> anyhow::Result<Selection> {
+        let groups = self.groups.iter().chain(&self.no_groups).chain(&self.only_groups);
+        check_defined("dependency group", groups, &config.dependency_groups)?;
+        check_defined("extra", &self.extras, &config.optional_dependencies)?;
+
+        if !self.only_groups.is_empty() {
+            return Ok(Selection {
+                dependencies: false,
+                groups: self.only_groups.iter().cloned().collect(),
+                extras: HashSet::new(),
+            });
+        }
+        let mut groups: HashSet<String> = self.groups.iter().cloned().collect();
+        if config.dependency_groups.contains_key(DEFAULT_GROUP) {
+            groups.insert(DEFAULT_GROUP.to_string());
+        }
+        for group in &self.no_groups {
+            groups.remove(group);
+        }
+        let extras = if self.all_extras {
+            config.optional_dependencies.keys().cloned().collect()
+        } else {
+            self.extras.iter().cloned().collect()
+        };
+        Ok(Selection {
+            dependencies: true,
+            groups,
+            extras,
+        })
+    }
+}
+
+/// Fails on the first of `names` that `wovenpkg.json` does not define.
+fn check_defined<'a, V>(
+    kind: &str,
+    names: impl IntoIterator<Item = &'a String>,
+    defined: &HashMap<String, V>,
+) -> anyhow::Result<()> {
+    let Some(unknown) = names.into_iter().find(|name| !defined.contains_key(*name)) else {
+        return Ok(());
+    };
+    let mut known: Vec<&str> = defined.keys().map(String::as_str).collect();
+    known.sort_unstable();
+    Err(anyhow::anyhow!(
+        "Unknown {kind} '{unknown}' (wovenpkg.json defines: {})",
+        if known.is_empty() {
+            "none".to_string()
+        } else {
+            known.join(", ")
+        }
+    ))
+}
+
+pub async fn execute(force_resolve: bool, args: &ResolveArgs, selection_args: &SelectionArgs) -> anyhow::Result<()> {
+    let mut config = config::read_config("wovenpkg.json")?;
+    args.apply(&mut config);
+    let selection = selection_args.selection(&config)?;
+    let force_resolve = force_resolve || args.is_set();
     let lock_path = Path::new("wovenpkg.lock");
     let cache = Cache::init()?;
 
@@ -59,25 +225,18 @@ pub async fn execute(force_resolve: bool) -> anyhow::Result<()> {
         venv_base.join("bin")
     };
 
-    let mut installed = HashSet::new();
-    if let Ok(entries) = std::fs::read_dir(&site_packages) {
-        for entry in entries.flatten() {
-            if let Some(name) = entry.file_name().to_str() {
-                if name.ends_with(".dist-info") {
-                    let pkg_name = name.split('-').next().unwrap_or("").to_lowercase().replace('-', "_");
-                    installed.insert(pkg_name);
-                }
-            }
-        }
-    }
+    let installed = installer::installed_packages(&site_packages);
 
     let reporter: Arc<dyn installer::InstallReporter> = CliProgressReporter::new();
 
     if lock_path.exists() && !force_resolve {
         ux::print_header("Synchronizing from lockfile...");
         let lockfile = Lockfile::read(lock_path)?;
+        // Registers index credentials before any artifact is downloaded.
+        let options = ResolveOptions::from_config(&config)?;
         let count = installer::install_from_lock(
             &lockfile,
+            &selection,
             &installed,
             &cache,
             packages_dir,
@@ -87,7 +246,14 @@ pub async fn execute(force_resolve: bool) -> anyhow::Result<()> {
         )
         .await?;
 
-        installer::prune_unused_packages(&site_packages, &lockfile, &reporter);
+        installer::prune_unused_packages(&site_packages, &lockfile, &selection, &reporter);
+
+        for (name, version, reason) in installer::newly_yanked(&lockfile, &options.indexes).await {
+            ux::print_warning(format!(
+                "{name} {version} has been yanked since it was locked{}. Run 'woven update' to move off it.",
+                reason.map(|r| format!(" ({r})")).unwrap_or_default()
+            ));
+        }
 
         if count > 0 {
             ux::print_success(format!("{count} packages ready."));
@@ -101,8 +267,9 @@ pub async fn execute(force_resolve: bool) -> anyhow::Result<()> {
             ux::print_header(&format!("Weaving dependency tree for {}", config.name));
         }
 
-        installer::resolve_and_install_final(
+        if let Err(e) = installer::resolve_and_install_final(
             &config,
+            &selection,
             &installed,
             &cache,
             packages_dir,
@@ -111,10 +278,16 @@ pub async fn execute(force_resolve: bool) -> anyhow::Result<()> {
             reporter.clone(),
             lock_path,
         )
-        .await?;
+        .await
+        {
+            if let WovenError::NoSolution(report) = &e {
+                ux::print_conflict_report(report);
+            }
+            return Err(e.into());
+        }
 
         let lockfile = Lockfile::read(lock_path)?;
-        installer::prune_unused_packages(&site_packages, &lockfile, &reporter);
+        installer::prune_unused_packages(&site_packages, &lockfile, &selection, &reporter);
 
         ux::print_success("Resolution complete.");
     }
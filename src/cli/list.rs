@@ -2,7 +2,7 @@ use std::path::Path;
 
 use crate::cli::ux;
 use crate::core::config;
-use crate::core::lock::Lockfile;
+use crate::core::lock::{self, Lockfile};
 use crate::core::python_manager;
 
 pub fn execute() -> anyhow::Result<()> {
@@ -20,7 +20,16 @@ pub fn execute() -> anyhow::Result<()> {
         pkgs.sort_by_key(|k| k.0);
 
         for (name, pkg) in pkgs {
-            println!(" \x1b[32m•\x1b[0m {} \x1b[90mv{}\x1b[0m", name, pkg.version);
+            let marker = pkg
+                .marker
+                .as_ref()
+                .map(|m| format!(" \x1b[90m; {m}\x1b[0m"))
+                .unwrap_or_default();
+            println!(
+                " \x1b[32m•\x1b[0m {} \x1b[90mv{}\x1b[0m{marker}",
+                lock::package_name(name),
+                pkg.version
+            );
         }
     } else {
         ux::print_warning("No lockfile found. Run 'woven install' to weave dependencies.");
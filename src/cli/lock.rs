@@ -0,0 +1,32 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::cli::install::ResolveArgs;
+use crate::cli::progress::CliProgressReporter;
+use crate::cli::ux;
+use crate::core::config;
+use crate::core::error::WovenError;
+use crate::core::installer::{self, InstallReporter};
+
+/// Resolves `wovenpkg.json` and writes `wovenpkg.lock` without touching the
+/// virtual environment.
+pub async fn execute(args: &ResolveArgs) -> anyhow::Result<()> {
+    let mut config = config::read_config("wovenpkg.json")?;
+    args.apply(&mut config);
+    ux::print_header(&format!("Locking dependencies for {}", config.name));
+
+    let reporter: Arc<dyn InstallReporter> = CliProgressReporter::new();
+    let lockfile = match installer::resolve_lockfile(&config, &reporter).await {
+        Ok(lockfile) => lockfile,
+        Err(e) => {
+            if let WovenError::NoSolution(report) = &e {
+                ux::print_conflict_report(report);
+            }
+            return Err(e.into());
+        }
+    };
+
+    lockfile.write(Path::new("wovenpkg.lock"))?;
+    ux::print_success(format!("Locked {} packages in wovenpkg.lock", lockfile.packages.len()));
+    Ok(())
+}
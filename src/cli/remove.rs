@@ -10,7 +10,30 @@ pub async fn execute(package_name: &str) -> anyhow::Result<()> {
     let config_path = "wovenpkg.json";
     let mut config = config::read_config(config_path)?;
 
-    if config.dependencies.remove(package_name).is_some() {
+    let name = config::dependency_name(package_name);
+    let matches = |k: &String| config::dependency_name(k) == name;
+
+    // The project's dependencies first, then every dependency group and extra listing the package.
+    let mut removed = false;
+    if let Some(key) = config.dependencies.keys().find(|k| matches(k)).cloned() {
+        removed |= config.dependencies.remove(&key).is_some();
+    }
+    for (group, deps) in &mut config.dependency_groups {
+        if let Some(key) = deps.keys().find(|k| matches(k)).cloned() {
+            deps.remove(&key);
+            ux::print_info(format!("Removed {package_name} from the {group} group"));
+            removed = true;
+        }
+    }
+    for (extra, deps) in &mut config.optional_dependencies {
+        if let Some(key) = deps.keys().find(|k| matches(k)).cloned() {
+            deps.remove(&key);
+            ux::print_info(format!("Removed {package_name} from the {extra} extra"));
+            removed = true;
+        }
+    }
+
+    if removed {
         // Write config back
         let new_json = serde_json::to_string_pretty(&config)?;
         fs::write(config_path, new_json)?;
@@ -18,7 +41,12 @@ pub async fn execute(package_name: &str) -> anyhow::Result<()> {
 
         // Use forced resolution after removal to ensure lockfile is consistent
         ux::print_info("Updating environment...");
-        install::execute(true).await?;
+        install::execute(
+            true,
+            &install::ResolveArgs::default(),
+            &install::SelectionArgs::default(),
+        )
+        .await?;
     } else {
         ux::print_error(format!("Package {package_name} not found in dependencies."));
     }
@@ -1,37 +1,117 @@
-use crate::cli::ux;
-use crate::core::config;
-use crate::dependencies::package;
+use std::collections::{BTreeSet, HashMap};
 
-pub async fn execute() -> anyhow::Result<()> {
-    let mut config = config::read_config("wovenpkg.json")?;
-    ux::print_header(&format!("Updating dependencies for {}", config.name));
+use crate::cli::install::{self, ResolveArgs, SelectionArgs};
+use crate::cli::{add, ux};
+use crate::core::config::{self, Config};
+use crate::core::marker;
+use crate::core::name::PackageName;
 
-    let deps: Vec<String> = config.dependencies.keys().cloned().collect();
-    let mut updated = false;
+/// Whether a `wovenpkg.json` constraint is looked up on the indexes. Direct
+/// references and local paths are pinned by their source.
+fn is_updatable(constraint: &str) -> bool {
+    config::direct_url(constraint).is_none() && config::local_path(constraint).is_none()
+}
 
-    for req_name in deps {
-        if let Ok(pypi_info) = package::fetch_package_info(&req_name, None).await {
-            let latest_version = pypi_info.info.version;
-            if let Some(current) = config.dependencies.get(&req_name) {
-                if current != &latest_version {
-                    ux::print_info(format!("Updating {} ({} -> {})", req_name, current, latest_version));
-                    config.dependencies.insert(req_name.clone(), latest_version);
-                    updated = true;
-                }
+/// Projects to look up: every updatable dependency, including those in
+/// dependency groups and extras, or only the ones named in `packages`.
+fn projects_to_update(config: &Config, packages: &[String]) -> anyhow::Result<BTreeSet<String>> {
+    let declared: Vec<(&String, &String)> = config.all_dependencies().collect();
+    for name in packages {
+        let name = PackageName::new(name);
+        if !declared.iter().any(|(key, _)| config::dependency_name(key) == name) {
+            return Err(anyhow::anyhow!("{name} is not a dependency of this project"));
+        }
+    }
+    let requested: Vec<PackageName> = packages.iter().map(|name| PackageName::new(name)).collect();
+    Ok(declared
+        .into_iter()
+        .filter(|(key, constraint)| {
+            is_updatable(constraint) && (requested.is_empty() || requested.contains(&config::dependency_name(key)))
+        })
+        .map(|(key, _)| marker::extract_package_name(key))
+        .collect())
+}
+
+/// Writes each version in `latest` into every table declaring that package,
+/// returning whether anything changed.
+fn apply_updates(config: &mut Config, latest: &HashMap<PackageName, String>) -> bool {
+    let tables = std::iter::once(&mut config.dependencies)
+        .chain(config.dependency_groups.values_mut())
+        .chain(config.optional_dependencies.values_mut());
+    let mut updated = false;
+    for table in tables {
+        for (key, current) in table.iter_mut() {
+            let Some(latest_version) = latest.get(&config::dependency_name(key)) else {
+                continue;
+            };
+            if is_updatable(current) && current != latest_version {
+                ux::print_info(format!("Updating {} ({} -> {})", key, current, latest_version));
+                current.clone_from(latest_version);
+                updated = true;
             }
+        }
+    }
+    updated
+}
+
+pub async fn execute(packages: &[String], args: &ResolveArgs) -> anyhow::Result<()> {
+    let mut config = config::read_config("wovenpkg.json")?;
+    let options = args.options(&config)?;
+    ux::print_header(&format!("Updating dependencies for {}", config.name));
+
+    let mut latest = HashMap::new();
+    for project in projects_to_update(&config, packages)? {
+        if let Ok(latest_version) = add::latest_compatible(&project, None, &options).await {
+            latest.insert(PackageName::new(&project), latest_version);
         } else {
-            ux::print_warning(format!("Could not fetch latest info for {}", req_name));
+            ux::print_warning(format!("Could not fetch latest info for {}", project));
         }
     }
 
-    if updated {
+    if apply_updates(&mut config, &latest) {
         config::write_config(&config, "wovenpkg.json")?;
     } else {
         ux::print_success("All tracked dependencies are already at their latest version.");
     }
 
     // Use forced resolution for update
-    crate::cli::install::execute(true).await?;
+    install::execute(true, args, &SelectionArgs::default()).await?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> Config {
+        Config {
+            dependencies: HashMap::from([
+                ("requests".into(), "2.31.0".into()),
+                ("demo".into(), "https://host.example/demo-1.0-py3-none-any.whl".into()),
+            ]),
+            dependency_groups: HashMap::from([(
+                "dev".into(),
+                HashMap::from([("pytest".into(), "7.4.0".into()), ("requests".into(), "2.31.0".into())]),
+            )]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn updates_packages_in_dependency_groups() {
+        let mut config = project();
+        let everything = projects_to_update(&config, &[]).unwrap();
+        assert_eq!(everything.into_iter().collect::<Vec<_>>(), ["pytest", "requests"]);
+
+        let named = projects_to_update(&config, &["PyTest".into()]).unwrap();
+        assert_eq!(named.into_iter().collect::<Vec<_>>(), ["pytest"]);
+        assert!(projects_to_update(&config, &["flask".into()]).is_err());
+
+        let latest = HashMap::from([(PackageName::new("pytest"), "8.2.0".to_string())]);
+        assert!(apply_updates(&mut config, &latest));
+        assert_eq!(config.dependency_groups["dev"]["pytest"], "8.2.0");
+        assert_eq!(config.dependency_groups["dev"]["requests"], "2.31.0");
+        assert!(!apply_updates(&mut config, &latest));
+    }
+}
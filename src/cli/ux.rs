@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use crate::core::resolver::ConflictReport;
+
 pub fn print_success<T: Display>(msg: T) {
     println!("\x1b[1m\x1b[32m✓\x1b[0m {msg}");
 }
@@ -19,6 +21,35 @@ pub fn print_info<T: Display>(msg: T) {
     println!("\x1b[1m\x1b[36m•\x1b[0m {msg}");
 }
 
+/// Prints why dependency resolution failed: each clashing requirement with the
+/// chain of packages that pulled it in, and the versions the resolver tried.
+pub fn print_conflict_report(report: &ConflictReport) {
+    eprintln!("\n\x1b[1m\x1b[31m✗ Dependency conflict\x1b[0m");
+
+    for conflict in &report.conflicts {
+        eprintln!("\n  No version of \x1b[1m{}\x1b[0m satisfies all of:", conflict.package);
+        for derivation in &conflict.requirements {
+            eprintln!(
+                "    \x1b[33m{}\x1b[0m \x1b[90m← {}\x1b[0m",
+                derivation.requirement,
+                derivation.chain.join(" → ")
+            );
+        }
+        for rejected in &conflict.rejected {
+            eprintln!("    \x1b[90m• {rejected}\x1b[0m");
+        }
+    }
+
+    if !report.tried.is_empty() {
+        eprintln!("\n  Versions tried:");
+        for (name, versions) in &report.tried {
+            eprintln!("    {name}: \x1b[90m{}\x1b[0m", versions.join(", "));
+        }
+    }
+
+    eprintln!("\n  \x1b[36mHint:\x1b[0m relax or remove one of the requirements above in wovenpkg.json.\n");
+}
+
 pub fn print_header(msg: &str) {
     println!("\n\x1b[1m\x1b[36m🐍 WovenSnake\x1b[0m \x1b[90m| {msg}\x1b[0m\n");
 }
@@ -1,6 +1,18 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Environment variable that moves every cache away from `~/.wovensnake/cache`.
+pub const CACHE_DIR_ENV: &str = "WOVEN_CACHE_DIR";
+
+/// Root of the package, metadata and git caches: `WOVEN_CACHE_DIR`, or
+/// `~/.wovensnake/cache`.
+pub fn cache_dir() -> Option<PathBuf> {
+    std::env::var_os(CACHE_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".wovensnake").join("cache")))
+}
+
 #[derive(Clone)]
 pub struct Cache {
     base_dir: PathBuf,
@@ -12,8 +24,7 @@ impl Cache {
     }
 
     pub fn init() -> Result<Self, crate::core::error::WovenError> {
-        let home = dirs::home_dir().ok_or("Could not find home directory")?;
-        let cache_dir = home.join(".wovensnake").join("cache");
+        let cache_dir = cache_dir().ok_or("Could not find home directory")?;
 
         if !cache_dir.exists() {
             fs::create_dir_all(&cache_dir)?;
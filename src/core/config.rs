@@ -1,9 +1,16 @@
+use pep508_rs::{Requirement, VerbatimUrl, VersionOrUrl};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::core::error::WovenError;
+use crate::core::marker;
+use crate::core::name::PackageName;
+use crate::core::timestamp::Timestamp;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Config {
     pub name: String,
     pub version: String,
@@ -11,6 +18,221 @@ pub struct Config {
     pub dependencies: HashMap<String, String>,
     #[serde(rename = "virtualEnvironment")]
     pub virtual_environment: String,
+    /// When the resolver may pick pre-release versions.
+    #[serde(default, skip_serializing_if = "PrereleaseMode::is_default")]
+    pub prerelease: PrereleaseMode,
+    /// Packages allowed to resolve to pre-releases under [`PrereleaseMode::AllowListed`].
+    #[serde(rename = "prereleasePackages", default, skip_serializing_if = "Vec::is_empty")]
+    pub prerelease_packages: Vec<String>,
+    /// Which end of each allowed version range the resolver prefers.
+    #[serde(default, skip_serializing_if = "ResolutionStrategy::is_default")]
+    pub resolution: ResolutionStrategy,
+    /// Ignore files uploaded after this moment, for reproducible re-resolution.
+    #[serde(rename = "excludeNewer", default, skip_serializing_if = "Option::is_none")]
+    pub exclude_newer: Option<Timestamp>,
+    /// Lock for every platform, recording markers that are evaluated at install time.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub universal: bool,
+    /// Version limits that apply whenever a package is pulled in, without adding it.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub constraints: HashMap<String, String>,
+    /// Requirements file with more constraints, one PEP 508 requirement per line.
+    #[serde(rename = "constraintsFile", default, skip_serializing_if = "Option::is_none")]
+    pub constraints_file: Option<String>,
+    /// Versions forced on a package regardless of the ranges other packages declare.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub overrides: HashMap<String, String>,
+    /// Requirements file with more overrides, in the same format as `constraintsFile`.
+    #[serde(rename = "overridesFile", default, skip_serializing_if = "Option::is_none")]
+    pub overrides_file: Option<String>,
+    /// Named sets of development dependencies, e.g. `dev` or `docs`, that are
+    /// locked with `dependencies` but installed only on request.
+    #[serde(rename = "dependencyGroups", default, skip_serializing_if = "HashMap::is_empty")]
+    pub dependency_groups: HashMap<String, HashMap<String, String>>,
+    /// The project's own extras, e.g. `postgres`: optional feature sets whose
+    /// dependencies are locked with `dependencies` and installed with `--extra`.
+    #[serde(rename = "optionalDependencies", default, skip_serializing_if = "HashMap::is_empty")]
+    pub optional_dependencies: HashMap<String, HashMap<String, String>>,
+    /// Simple API root of the package index, e.g. `https://devpi.example.com/root/pypi/+simple`.
+    #[serde(rename = "indexUrl", default, skip_serializing_if = "Option::is_none")]
+    pub index_url: Option<String>,
+    /// Further indexes, searched in order before `indexUrl`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<IndexConfig>,
+    /// Where individual packages must come from, keyed by package name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sources: HashMap<String, PackageSourceConfig>,
+    /// Wheelhouse directories, HTML files or flat HTML pages searched before
+    /// every index, e.g. `./wheels`.
+    #[serde(rename = "findLinks", default, skip_serializing_if = "Vec::is_empty")]
+    pub find_links: Vec<String>,
+}
+
+impl Config {
+    /// Constraints from `constraintsFile` merged with the inline `constraints`,
+    /// which win when both name a package.
+    pub fn constraints(&self) -> Result<HashMap<String, String>, WovenError> {
+        merge_requirements(self.constraints_file.as_deref(), &self.constraints)
+    }
+
+    /// Overrides from `overridesFile` merged with the inline `overrides`.
+    pub fn overrides(&self) -> Result<HashMap<String, String>, WovenError> {
+        merge_requirements(self.overrides_file.as_deref(), &self.overrides)
+    }
+
+    /// Every declared dependency: the base ones, then each dependency group
+    /// and each of the project's extras.
+    pub fn all_dependencies(&self) -> impl Iterator<Item = (&String, &String)> {
+        let named_sets = self
+            .dependency_groups
+            .values()
+            .chain(self.optional_dependencies.values());
+        self.dependencies.iter().chain(named_sets.flat_map(HashMap::iter))
+    }
+
+    /// Packages declared as editable paths (`"-e ../libs/foo"`) anywhere in
+    /// `wovenpkg.json`.
+    pub fn editable_packages(&self) -> HashSet<PackageName> {
+        self.all_dependencies()
+            .filter(|(_, constraint)| local_path(constraint).is_some_and(|local| local.editable))
+            .map(|(key, _)| dependency_name(key))
+            .collect()
+    }
+}
+
+/// The package a `wovenpkg.json` dependency key names, e.g. `zope-interface`
+/// for `Zope.Interface` or `requests` for `requests[socks]`.
+pub fn dependency_name(key: &str) -> PackageName {
+    PackageName::new(&marker::extract_package_name(key))
+}
+
+/// The URL of a `wovenpkg.json` dependency given as a direct reference, e.g.
+/// `"pkg": "https://host/pkg-1.0-py3-none-any.whl"`, or `None` for a version constraint.
+pub fn direct_url(constraint: &str) -> Option<&str> {
+    let constraint = constraint.trim();
+    let constraint = constraint.strip_prefix('@').map_or(constraint, str::trim_start);
+    constraint.contains("://").then_some(constraint)
+}
+
+/// A `wovenpkg.json` dependency on a local project, e.g. `"../libs/foo"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalPath<'a> {
+    /// The path as written, relative to the project directory unless absolute.
+    pub path: &'a str,
+    /// Written as `"-e ../libs/foo"`: installed by linking the sources into
+    /// site-packages instead of copying a build of them.
+    pub editable: bool,
+}
+
+/// The local path of a dependency given as `"../libs/foo"`, `"./foo"`, an
+/// absolute path, or any of those behind `-e`; `None` for anything else.
+pub fn local_path(constraint: &str) -> Option<LocalPath<'_>> {
+    let constraint = constraint.trim();
+    let (path, editable) = constraint
+        .strip_prefix("-e ")
+        .map_or((constraint, false), |rest| (rest.trim_start(), true));
+    let is_path = path.starts_with("./")
+        || path.starts_with("../")
+        || path == "."
+        || path == ".."
+        || std::path::Path::new(path).is_absolute();
+    is_path.then_some(LocalPath { path, editable })
+}
+
+fn merge_requirements(
+    file: Option<&str>,
+    inline: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, WovenError> {
+    // Keyed by normalized name, so `Foo_Bar` inline replaces `foo-bar` from the file.
+    let mut merged: HashMap<PackageName, (String, String)> = HashMap::new();
+    let file = match file {
+        Some(path) => read_requirements_file(path)?,
+        None => HashMap::new(),
+    };
+    for (name, spec) in file.into_iter().chain(inline.clone()) {
+        merged.insert(PackageName::new(&name), (name, spec));
+    }
+    Ok(merged.into_values().collect())
+}
+
+/// Reads a `requirements.txt`-style file of version requirements such as
+/// `urllib3<2`, returning name → specifiers.
+///
+/// Blank lines and `#` comments are ignored. Environment markers are
+/// rejected, since constraints and overrides apply on every platform.
+pub fn read_requirements_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, String>, WovenError> {
+    let path = path.as_ref();
+    let content =
+        fs::read_to_string(path).map_err(|e| WovenError::Config(format!("cannot read {}: {e}", path.display())))?;
+    let mut requirements = HashMap::new();
+    for line in content.lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let req = Requirement::<VerbatimUrl>::from_str(line)
+            .map_err(|e| WovenError::Config(format!("{}: invalid requirement '{line}': {e}", path.display())))?;
+        if !req.marker.is_true() {
+            return Err(WovenError::Config(format!(
+                "{}: '{line}' has an environment marker, which constraints and overrides do not support",
+                path.display()
+            )));
+        }
+        let specifiers = match req.version_or_url {
+            Some(VersionOrUrl::VersionSpecifier(spec)) => spec.to_string(),
+            Some(VersionOrUrl::Url(_)) => {
+                return Err(WovenError::Config(format!(
+                    "{}: '{line}' must be a version requirement",
+                    path.display()
+                )))
+            }
+            None => String::new(),
+        };
+        requirements.insert(req.name.to_string(), specifiers);
+    }
+    Ok(requirements)
+}
+
+/// A named package index from `"indexes"` in `wovenpkg.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IndexConfig {
+    pub name: String,
+    /// Simple API root, e.g. `https://pkgs.example.com/simple`.
+    pub url: String,
+    /// Only consulted for packages pinned to it in `"sources"`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub explicit: bool,
+}
+
+/// Where one package comes from, from `"sources"` in `wovenpkg.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PackageSourceConfig {
+    /// Name of the index in `"indexes"` the package is taken from, and only from.
+    pub index: String,
+}
+
+/// Pre-release policy, set with `"prerelease"` in `wovenpkg.json` or `--prerelease`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum PrereleaseMode {
+    /// Pre-releases only when no stable release satisfies the requirements.
+    #[default]
+    IfNecessary,
+    /// Never select a pre-release.
+    Disallow,
+    /// Pre-releases only for packages whose specifiers name one, e.g. `>=2.0b1`.
+    AllowIfExplicit,
+    /// Pre-releases only for the packages listed in `prereleasePackages`.
+    AllowListed,
+    /// Treat pre-releases like any other release.
+    AllowAll,
+}
+
+impl PrereleaseMode {
+    #[allow(clippy::trivially_copy_pass_by_ref)] // signature required by `skip_serializing_if`
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 pub fn read_config<P: AsRef<Path>>(path: P) -> Result<Config, crate::core::error::WovenError> {
@@ -24,3 +246,23 @@ pub fn write_config<P: AsRef<Path>>(config: &Config, path: P) -> Result<(), crat
     fs::write(path, content)?;
     Ok(())
 }
+
+/// Version preference, set with `"resolution"` in `wovenpkg.json` or `--resolution`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResolutionStrategy {
+    /// Newest version allowed for every package.
+    #[default]
+    Highest,
+    /// Oldest version allowed for every package.
+    Lowest,
+    /// Oldest version allowed for direct dependencies, newest for the rest.
+    LowestDirect,
+}
+
+impl ResolutionStrategy {
+    #[allow(clippy::trivially_copy_pass_by_ref)] // signature required by `skip_serializing_if`
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
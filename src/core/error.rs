@@ -29,6 +29,9 @@ pub enum WovenError {
     #[error("Package resolution logic failed: {0}")]
     ResolutionConflict(String),
 
+    #[error("Dependency conflict: {0}")]
+    NoSolution(Box<crate::core::resolver::ConflictReport>),
+
     #[error("Lockfile error: {0}")]
     Lockfile(String),
 
@@ -38,6 +41,12 @@ pub enum WovenError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    #[error("Git operation failed: {0}")]
+    Git(String),
+
+    #[error("Offline, and not available locally:\n  - {}", .0.join("\n  - "))]
+    Offline(Vec<String>),
+
     #[error("Async task join error: {0}")]
     Join(#[from] tokio::task::JoinError),
 
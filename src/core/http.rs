@@ -11,3 +11,10 @@ pub static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
         .build()
         .expect("failed to build reqwest::Client")
 });
+
+/// A GET request for `url`, carrying the credentials known for its host.
+///
+/// Use this for index pages, metadata and package downloads; see [`crate::core::auth`].
+pub fn get(url: &str) -> reqwest::RequestBuilder {
+    crate::core::auth::authorize(CLIENT.get(url), url)
+}
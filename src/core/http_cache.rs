@@ -0,0 +1,230 @@
+//! On-disk cache for index responses such as `https://pypi.org/simple/{name}/`.
+//!
+//! Each response is stored with its `ETag` and `Last-Modified` headers. Within
+//! the freshness window it is served without touching the network; after
+//! that it is revalidated with a conditional request, so an unchanged project
+//! costs a `304 Not Modified` instead of a full download.
+
+use once_cell::sync::Lazy;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::error::WovenError;
+
+/// Freshness window used when `WOVEN_METADATA_TTL` is not set.
+const DEFAULT_TTL: Duration = Duration::from_mins(10);
+
+/// Process-wide metadata cache under `~/.wovensnake/cache/http`, or `http`
+/// under `WOVEN_CACHE_DIR`.
+///
+/// The freshness window is read from `WOVEN_METADATA_TTL` (seconds); `0`
+/// revalidates on every request.
+pub static METADATA: Lazy<HttpCache> = Lazy::new(|| {
+    let ttl = std::env::var("WOVEN_METADATA_TTL")
+        .ok()
+        .and_then(|secs| secs.trim().parse().ok())
+        .map_or(DEFAULT_TTL, Duration::from_secs);
+    HttpCache::new(crate::core::cache::cache_dir().map(|dir| dir.join("http")), ttl)
+});
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Entry {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    /// Unix seconds when the body was last confirmed current.
+    fetched_at: u64,
+    body: String,
+}
+
+/// A response body with the `Content-Type` it was served with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    pub body: String,
+    pub content_type: Option<String>,
+}
+
+pub struct HttpCache {
+    /// `None` disables caching, e.g. when there is no home directory.
+    dir: Option<PathBuf>,
+    ttl: Duration,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+impl HttpCache {
+    pub const fn new(dir: Option<PathBuf>, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    fn path(&self, url: &str) -> Option<PathBuf> {
+        let digest = format!("{:x}", Sha256::digest(url.as_bytes()));
+        Some(self.dir.as_ref()?.join(format!("{digest}.json")))
+    }
+
+    async fn load(&self, url: &str) -> Option<Entry> {
+        let raw = tokio::fs::read(self.path(url)?).await.ok()?;
+        serde_json::from_slice::<Entry>(&raw).ok().filter(|e| e.url == url)
+    }
+
+    /// Best effort: a cache that cannot be written only costs a refetch later.
+    async fn store(&self, entry: &Entry) {
+        let (Some(dir), Some(path)) = (&self.dir, self.path(&entry.url)) else {
+            return;
+        };
+        let Ok(json) = serde_json::to_vec(entry) else {
+            return;
+        };
+        if tokio::fs::create_dir_all(dir).await.is_ok() {
+            let tmp = path.with_extension("tmp");
+            if tokio::fs::write(&tmp, json).await.is_ok() {
+                let _ = tokio::fs::rename(&tmp, &path).await;
+            }
+        }
+    }
+
+    fn is_fresh(&self, entry: &Entry) -> bool {
+        now().saturating_sub(entry.fetched_at) < self.ttl.as_secs()
+    }
+
+    /// A cached body for `url` still inside the freshness window.
+    pub async fn fresh(&self, url: &str) -> Option<String> {
+        self.load(url).await.filter(|e| self.is_fresh(e)).map(|e| e.body)
+    }
+
+    /// Body of `url`, from the cache while fresh and revalidated otherwise.
+    /// Returns `None` when the server answers 404 or 410; any other error
+    /// status is an error, so an outage is never mistaken for a missing page.
+    pub async fn fetch(&self, url: &str) -> Result<Option<String>, WovenError> {
+        Ok(self.fetch_document(url, None).await?.map(|document| document.body))
+    }
+
+    /// Like [`HttpCache::fetch`], sending `accept` as the `Accept` header and
+    /// keeping the `Content-Type` the server chose.
+    pub async fn fetch_document(&self, url: &str, accept: Option<&str>) -> Result<Option<Document>, WovenError> {
+        let cached = self.load(url).await;
+        if let Some(entry) = cached.as_ref().filter(|e| self.is_fresh(e)) {
+            return Ok(Some(entry.clone().into()));
+        }
+        if crate::core::offline::is_enabled() {
+            // However stale, the cached body is the best there is.
+            return cached
+                .map(|entry| Some(entry.into()))
+                .ok_or_else(|| WovenError::Offline(vec![format!("{url} (not in the metadata cache)")]));
+        }
+
+        let mut request = crate::core::http::get(url);
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, modified);
+            }
+        }
+        let response = request.send().await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(mut entry) = cached {
+                entry.fetched_at = now();
+                self.store(&entry).await;
+                return Ok(Some(entry.into()));
+            }
+        }
+        if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+            // Not a miss: falling through to another index here would defeat index pinning.
+            return Err(format!("{url}: {} (check the index credentials)", response.status()).into());
+        }
+        if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("{}: {}", crate::core::auth::redact(url), response.status()).into());
+        }
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(ToString::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let content_type = header(CONTENT_TYPE);
+        let entry = Entry {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            content_type,
+            fetched_at: now(),
+            body: response.text().await?,
+        };
+        self.store(&entry).await;
+        Ok(Some(entry.into()))
+    }
+}
+
+impl From<Entry> for Document {
+    fn from(entry: Entry) -> Self {
+        Self {
+            body: entry.body,
+            content_type: entry.content_type,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, fetched_at: u64) -> Entry {
+        Entry {
+            url: url.to_string(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+            content_type: None,
+            fetched_at,
+            body: "{}".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serves_entries_inside_freshness_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(Some(dir.path().to_path_buf()), Duration::from_mins(1));
+        let url = "https://pypi.org/pypi/requests/json";
+
+        assert_eq!(cache.fresh(url).await, None);
+        cache.store(&entry(url, now())).await;
+        assert_eq!(cache.fresh(url).await.as_deref(), Some("{}"));
+        assert_eq!(cache.fresh("https://pypi.org/pypi/flask/json").await, None);
+
+        cache.store(&entry(url, now() - 120)).await;
+        assert_eq!(cache.fresh(url).await, None);
+        assert_eq!(cache.load(url).await.unwrap().etag.as_deref(), Some("\"abc\""));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_stores_nothing() {
+        let cache = HttpCache::new(None, Duration::from_mins(1));
+        let url = "https://pypi.org/pypi/requests/json";
+        cache.store(&entry(url, now())).await;
+        assert_eq!(cache.fresh(url).await, None);
+    }
+}
@@ -9,9 +9,12 @@ use sha2::{Digest, Sha256};
 use crate::core::cache::Cache;
 use crate::core::config;
 use crate::core::error::WovenError;
-use crate::core::lock::{Artifact, LockedPackage, Lockfile};
+use crate::core::lock::{self, Artifact, LockedPackage, Lockfile, Selection};
+use crate::core::name::PackageName;
+use crate::core::offline;
 use crate::core::selection::select_artifact;
-use crate::dependencies::package;
+use crate::dependencies::index::{self, Index, Indexes};
+use crate::dependencies::{build, git, package};
 
 pub trait InstallReporter: Send + Sync {
     fn create_task(&self, name: &str) -> Box<dyn InstallTaskReporter>;
@@ -69,7 +72,8 @@ pub fn platform_from_filename(filename: &str) -> String {
 
 pub async fn install_from_lock<S: std::hash::BuildHasher + Sync>(
     lockfile: &Lockfile,
-    installed: &HashSet<String, S>,
+    selection: &Selection,
+    installed: &HashSet<PackageName, S>,
     cache: &Cache,
     packages_dir: &Path,
     site_packages: &Path,
@@ -77,10 +81,16 @@ pub async fn install_from_lock<S: std::hash::BuildHasher + Sync>(
     reporter: Arc<dyn InstallReporter>,
 ) -> Result<usize, WovenError> {
     let packages_to_install: Vec<_> = lockfile
-        .packages
-        .iter()
-        .filter(|(name, _)| !installed.contains(&name.to_lowercase().replace('-', "_")))
+        .packages_to_install(selection)
+        .into_iter()
+        .filter(|(name, pkg)| !installed.contains(name) || source_changed(site_packages, name, pkg))
         .collect();
+    if offline::is_enabled() {
+        let missing = missing_offline(&packages_to_install, cache, packages_dir);
+        if !missing.is_empty() {
+            return Err(WovenError::Offline(missing));
+        }
+    }
 
     let count = Arc::new(AtomicUsize::new(0));
     let cache_arc = Arc::new(cache.clone());
@@ -92,7 +102,6 @@ pub async fn install_from_lock<S: std::hash::BuildHasher + Sync>(
             let packages_dir = packages_dir.to_path_buf();
             let scripts_dir = scripts_dir.to_path_buf();
             let python_version = lockfile.python_version.clone();
-            let name = name.clone();
             let pkg = pkg.clone();
             let reporter = Arc::clone(&reporter);
             let cache = Arc::clone(&cache_arc);
@@ -102,23 +111,32 @@ pub async fn install_from_lock<S: std::hash::BuildHasher + Sync>(
 
                 if let Some(artifact) = select_artifact(&pkg.artifacts, current_platform()) {
                     let dest_path = packages_dir.join(&artifact.filename);
+                    if pkg.is_source_build() {
+                        // A build of older sources may carry the same file name.
+                        let _ = tokio::fs::remove_file(&dest_path).await;
+                    }
 
                     if cache.contains(&artifact.filename, &artifact.sha256) {
                         if let Err(e) = cache.link_to_project(&artifact.filename, &artifact.sha256, &packages_dir) {
                             task.warning(format!("{name}: cache link failed ({e})"));
                         }
-                    } else if !dest_path.exists() {
-                        let res = match crate::core::http::CLIENT.get(&artifact.url).send().await {
-                            Ok(r) => r,
+                    } else if pkg.is_source_build() {
+                        let data = match rebuild(&artifact.url, &python_version).await {
+                            Ok(data) => data,
                             Err(e) => {
-                                task.finish_error(format!("{name}: request failed ({e})"));
+                                task.finish_error(format!("{name}: build failed ({e})"));
                                 return;
                             }
                         };
-                        let data = match res.bytes().await {
-                            Ok(d) => d,
+                        if let Err(e) = tokio::fs::write(&dest_path, &data).await {
+                            task.finish_error(format!("{name}: write failed ({e})"));
+                            return;
+                        }
+                    } else if !dest_path.exists() {
+                        let data = match fetch_artifact(&artifact.url).await {
+                            Ok(data) => data,
                             Err(e) => {
-                                task.finish_error(format!("{name}: download failed ({e})"));
+                                task.finish_error(format!("{name}: {e}"));
                                 return;
                             }
                         };
@@ -136,17 +154,16 @@ pub async fn install_from_lock<S: std::hash::BuildHasher + Sync>(
                         }
                     }
 
+                    if pkg.is_source_build() {
+                        remove_dist_info(&site_packages, &name);
+                    }
                     let is_wheel = artifact.filename.to_lowercase().ends_with(".whl");
                     let dest_path_clone = dest_path.clone();
                     let site_packages_clone = site_packages.clone();
+                    let editable = editable_source(&pkg);
 
                     let extract_result = tokio::task::spawn_blocking(move || {
-                        let res = if is_wheel {
-                            package::extract_wheel(&dest_path_clone, &site_packages_clone)
-                        } else {
-                            package::extract_targz(&dest_path_clone, &site_packages_clone)
-                        };
-                        res.map_err(|e| e.to_string())
+                        unpack(&dest_path_clone, &site_packages_clone, editable.as_deref()).map_err(|e| e.to_string())
                     })
                     .await
                     .unwrap_or_else(|e| Err(format!("Task joined failed: {e}")));
@@ -161,10 +178,13 @@ pub async fn install_from_lock<S: std::hash::BuildHasher + Sync>(
                             if let Err(e) = package::generate_scripts(&dist_info, &scripts_dir, &python_version) {
                                 task.warning(format!("{name}: script generation failed ({e})"));
                             }
+                            if let Err(e) = record_direct_url(&dist_info, &pkg) {
+                                task.warning(format!("{name}: could not record its source ({e})"));
+                            }
                         }
                     }
                 }
-                task.finish_success(name.clone());
+                task.finish_success(name.to_string());
                 count.fetch_add(1, Ordering::SeqCst);
             }
         })
@@ -173,64 +193,168 @@ pub async fn install_from_lock<S: std::hash::BuildHasher + Sync>(
     Ok(count.load(Ordering::SeqCst))
 }
 
-pub async fn resolve_and_install_final<S: std::hash::BuildHasher + Sync>(
-    config: &config::Config,
-    installed_project: &HashSet<String, S>,
-    cache: &Cache,
-    packages_dir: &Path,
-    site_packages: &Path,
-    scripts_dir: &Path,
-    reporter: Arc<dyn InstallReporter>,
-    lock_path: &Path,
-) -> Result<usize, WovenError> {
-    let mut lockfile = Lockfile::new(&config.name, &config.version, &config.python_version);
-    let mut local_installed: HashSet<String> = installed_project.iter().cloned().collect();
+/// Locked releases yanked on the index since the lockfile was written.
+///
+/// Returns `(name, version, reason)` tuples. Lookups that fail are ignored:
+/// the check is advisory and must not block an install.
+pub async fn newly_yanked(lockfile: &Lockfile, indexes: &Indexes) -> Vec<(String, String, Option<String>)> {
+    let mut yanked: Vec<_> = stream::iter(lockfile.packages.iter().filter(|(_, pkg)| !pkg.yanked))
+        .map(|(key, pkg)| async move {
+            let name = lock::package_name(key);
+            let files = match &pkg.index {
+                Some(url) => Index::new(url).release_files(&name, &pkg.version).await,
+                None => indexes.release_files(&name, &pkg.version).await,
+            }
+            .ok()?;
+            package::release_yanked(&files)
+                .then(|| (name.to_string(), pkg.version.clone(), package::yanked_reason(&files)))
+        })
+        .buffer_unordered(8)
+        .filter_map(|found| async move { found })
+        .collect()
+        .await;
+    yanked.sort();
+    yanked
+}
 
+/// Resolves `config` into a lockfile without installing anything.
+///
+/// Releases skipped for the project's Python and yanked releases selected
+/// through an exact pin are reported through `reporter`.
+pub async fn resolve_lockfile(
+    config: &config::Config,
+    reporter: &Arc<dyn InstallReporter>,
+) -> Result<Lockfile, WovenError> {
     let task = reporter.create_spinner("Solving dependencies...");
 
-    let graph = crate::core::resolver::resolve(&config.dependencies, &config.python_version).await?;
-    task.set_message("Dependency tree resolved. Satisfying packages...".to_string());
+    let graph = match crate::core::resolver::resolve(config).await {
+        Ok(graph) => graph,
+        Err(e) => {
+            task.finish_error("Dependency resolution failed.".to_string());
+            return Err(e);
+        }
+    };
+    for skipped in &graph.skipped {
+        task.print_line(format!(
+            "Skipped {} {}: {}",
+            skipped.package,
+            skipped.version,
+            skipped.reason.describe(&config.python_version)
+        ));
+    }
+    if !graph.undated.is_empty() {
+        task.warning(format!(
+            "The excludeNewer cutoff does not apply to {}: the index gives no upload times",
+            graph.undated.join(", ")
+        ));
+    }
+    for node in graph.packages.values().filter(|n| n.yanked) {
+        task.warning(format!(
+            "{} {} is yanked{}; using it because it is pinned exactly",
+            node.name,
+            node.version,
+            node.yanked_reason
+                .as_ref()
+                .map(|r| format!(" ({r})"))
+                .unwrap_or_default()
+        ));
+    }
 
-    let mut installed_count = 0;
+    let editable = config.editable_packages();
 
-    for (name_lower, node) in graph.packages {
+    let mut lockfile = Lockfile::new(&config.name, &config.version, &config.python_version);
+    for (key, node) in graph.packages {
         let mut artifacts: Vec<Artifact> = Vec::new();
         for url in node.urls {
-            if url.packagetype == "bdist_wheel" {
-                let platform = platform_from_filename(&url.filename);
-                artifacts.push(Artifact {
-                    url: url.url,
-                    filename: url.filename,
-                    sha256: url.digests.sha256,
-                    platform,
-                });
-            } else if url.packagetype == "sdist" {
-                artifacts.push(Artifact {
-                    url: url.url,
-                    filename: url.filename,
-                    sha256: url.digests.sha256,
-                    platform: "source".to_string(),
-                });
-            }
+            let platform = match url.packagetype.as_str() {
+                "bdist_wheel" => platform_from_filename(&url.filename),
+                "sdist" => "source".to_string(),
+                _ => continue,
+            };
+            let sha256 = if url.digests.sha256.is_empty() {
+                hash_unlisted(&url.url, &url.filename).await?
+            } else {
+                url.digests.sha256
+            };
+            artifacts.push(Artifact {
+                url: url.url,
+                filename: url.filename,
+                sha256,
+                platform,
+            });
         }
 
-        let node_name = node.name;
+        let name = lock::package_name(&key);
+        let editable = node.path.is_some() && editable.contains(&name);
+        let key = lock::package_key(&name, key.contains("==").then_some(node.version.as_str()));
         lockfile.packages.insert(
-            node_name.clone(),
+            key,
             LockedPackage {
                 version: node.version,
-                artifacts: artifacts.clone(),
+                artifacts,
                 dependencies: node.dependencies,
+                extras: node.extras,
+                yanked: node.yanked,
+                yanked_reason: node.yanked_reason,
+                overridden: node.overridden,
+                marker: node.marker,
+                commit: node.commit,
+                editable,
+                path: node.path,
+                groups: node.groups,
+                index: node.index,
+                project_extras: node.project_extras,
+                optional: node.optional,
             },
         );
+    }
+
+    task.finish_success(format!("Resolved {} packages.", lockfile.packages.len()));
+    Ok(lockfile)
+}
+
+pub async fn resolve_and_install_final<S: std::hash::BuildHasher + Sync>(
+    config: &config::Config,
+    selection: &Selection,
+    installed_project: &HashSet<PackageName, S>,
+    cache: &Cache,
+    packages_dir: &Path,
+    site_packages: &Path,
+    scripts_dir: &Path,
+    reporter: Arc<dyn InstallReporter>,
+    lock_path: &Path,
+) -> Result<usize, WovenError> {
+    let lockfile = resolve_lockfile(config, &reporter).await?;
+    let mut local_installed: HashSet<PackageName> = installed_project.iter().cloned().collect();
+    if offline::is_enabled() {
+        let mut packages = lockfile.packages_to_install(selection);
+        packages.retain(|(name, _)| !local_installed.contains(name));
+        let missing = missing_offline(&packages, cache, packages_dir);
+        if !missing.is_empty() {
+            return Err(WovenError::Offline(missing));
+        }
+    }
 
-        if let Some(pkg_url) = select_artifact(&artifacts, current_platform()) {
-            if local_installed.insert(name_lower) {
+    let task = reporter.create_spinner("Satisfying packages...");
+    let mut installed_count = 0;
+
+    for (node_name, locked) in lockfile.packages_to_install(selection) {
+        if let Some(pkg_url) = select_artifact(&locked.artifacts, current_platform()) {
+            if local_installed.insert(node_name.clone()) || source_changed(site_packages, &node_name, locked) {
                 let dest_path = packages_dir.join(&pkg_url.filename);
-                if !cache.contains(&pkg_url.filename, &pkg_url.sha256) && !dest_path.exists() {
+                if locked.is_source_build() {
+                    // A build of older sources may carry the same file name.
+                    let _ = tokio::fs::remove_file(&dest_path).await;
+                }
+                if !cache.contains(&pkg_url.filename, &pkg_url.sha256) && locked.is_source_build() {
+                    task.set_message(format!("Building: {node_name}"));
+                    tokio::fs::write(&dest_path, rebuild(&pkg_url.url, &config.python_version).await?).await?;
+                } else if !cache.contains(&pkg_url.filename, &pkg_url.sha256) && !dest_path.exists() {
                     task.set_message(format!("Downloading: {node_name}"));
-                    package::download_package(&pkg_url.url, &dest_path).await?;
-                    let data = tokio::fs::read(&dest_path).await?;
+                    let data = fetch_artifact(&pkg_url.url)
+                        .await
+                        .map_err(|e| format!("{node_name}: {e}"))?;
+                    tokio::fs::write(&dest_path, &data).await?;
                     if let Err(e) = cache.save(&pkg_url.filename, &pkg_url.sha256, &data) {
                         task.warning(format!("Cache save failed for {node_name}: {e}"));
                     }
@@ -241,16 +365,15 @@ pub async fn resolve_and_install_final<S: std::hash::BuildHasher + Sync>(
                 }
 
                 task.set_message(format!("Installing: {node_name}"));
+                if locked.is_source_build() {
+                    remove_dist_info(site_packages, &node_name);
+                }
                 let is_wheel = pkg_url.filename.to_lowercase().ends_with(".whl");
                 let site_packages_clone = site_packages.to_path_buf();
+                let editable = editable_source(locked);
 
                 let ext_res = tokio::task::spawn_blocking(move || {
-                    let res = if is_wheel {
-                        package::extract_wheel(&dest_path, &site_packages_clone)
-                    } else {
-                        package::extract_targz(&dest_path, &site_packages_clone)
-                    };
-                    res.map_err(|e| e.to_string())
+                    unpack(&dest_path, &site_packages_clone, editable.as_deref()).map_err(|e| e.to_string())
                 })
                 .await
                 .unwrap_or_else(|e| Err(format!("Task joined failed: {e}")));
@@ -263,6 +386,9 @@ pub async fn resolve_and_install_final<S: std::hash::BuildHasher + Sync>(
                         if let Err(e) = package::generate_scripts(&dist_info, scripts_dir, &config.python_version) {
                             task.warning(format!("Script generation failed for {node_name}: {e}"));
                         }
+                        if let Err(e) = record_direct_url(&dist_info, locked) {
+                            task.warning(format!("Could not record the source of {node_name}: {e}"));
+                        }
                     }
                 }
                 installed_count += 1;
@@ -275,41 +401,209 @@ pub async fn resolve_and_install_final<S: std::hash::BuildHasher + Sync>(
     Ok(installed_count)
 }
 
-pub fn find_dist_info(site_packages: &Path, name: &str) -> Option<PathBuf> {
-    let normalized = name.to_lowercase().replace('-', "_");
+pub fn find_dist_info(site_packages: &Path, name: &PackageName) -> Option<PathBuf> {
     std::fs::read_dir(site_packages)
         .ok()?
         .filter_map(std::result::Result::ok)
-        .find(|e| {
-            let fname = e.file_name().to_string_lossy().to_lowercase().replace('-', "_");
-            fname.starts_with(&normalized) && fname.ends_with(".dist-info")
-        })
+        .find(|e| PackageName::from_dist_info(&e.file_name().to_string_lossy()).as_ref() == Some(name))
         .map(|e| e.path())
 }
 
-pub fn prune_unused_packages(site_packages: &Path, lockfile: &Lockfile, reporter: &Arc<dyn InstallReporter>) {
+/// Names of the distributions installed in `site_packages`.
+pub fn installed_packages(site_packages: &Path) -> HashSet<PackageName> {
+    std::fs::read_dir(site_packages)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| PackageName::from_dist_info(&entry.file_name().to_string_lossy()))
+        .collect()
+}
+
+/// PEP 610 `direct_url.json` for a package built from a git checkout or a
+/// local directory; `None` for packages downloaded from an index.
+fn direct_url_for(locked: &LockedPackage) -> Option<serde_json::Value> {
+    if let Some(commit) = &locked.commit {
+        let url = locked.artifacts.first().map(|a| a.url.as_str()).unwrap_or_default();
+        let (repository, subdirectory) = git::GitUrl::parse(url).map_or_else(
+            |_| (url.to_string(), None),
+            |parsed| (parsed.repository, parsed.subdirectory),
+        );
+        let mut direct_url = serde_json::json!({
+            "url": repository,
+            "vcs_info": { "vcs": "git", "commit_id": commit },
+        });
+        if let Some(dir) = subdirectory {
+            direct_url["subdirectory"] = dir.into();
+        }
+        return Some(direct_url);
+    }
+
+    let path = locked.path.as_deref()?;
+    let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    let url = format!("file://{}", absolute.display());
+    Some(if absolute.is_dir() {
+        serde_json::json!({ "url": url, "dir_info": { "editable": locked.editable } })
+    } else {
+        serde_json::json!({ "url": url, "archive_info": {} })
+    })
+}
+
+/// Whether a git or path package is installed from a different commit,
+/// directory or mode than the lock asks for.
+fn source_changed(site_packages: &Path, name: &PackageName, locked: &LockedPackage) -> bool {
+    direct_url_for(locked).is_some_and(|wanted| installed_direct_url(site_packages, name).as_ref() != Some(&wanted))
+}
+
+fn installed_direct_url(site_packages: &Path, name: &PackageName) -> Option<serde_json::Value> {
+    let raw = std::fs::read_to_string(find_dist_info(site_packages, name)?.join("direct_url.json")).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+fn record_direct_url(dist_info: &Path, locked: &LockedPackage) -> std::io::Result<()> {
+    direct_url_for(locked).map_or(Ok(()), |direct_url| {
+        std::fs::write(dist_info.join("direct_url.json"), direct_url.to_string())
+    })
+}
+
+/// Project directory to link an editable path dependency to.
+fn editable_source(locked: &LockedPackage) -> Option<PathBuf> {
+    locked
+        .path
+        .as_deref()
+        .filter(|_| locked.editable)
+        .map(PathBuf::from)
+        .filter(|path| path.is_dir())
+}
+
+/// Installs an artifact into site-packages: wheels and sdists are unpacked,
+/// and editable path dependencies only get their metadata and a `.pth` link.
+fn unpack(archive: &Path, site_packages: &Path, editable: Option<&Path>) -> Result<(), WovenError> {
+    let is_wheel = archive.to_string_lossy().to_lowercase().ends_with(".whl");
+    match editable {
+        Some(source) => package::install_editable(archive, site_packages, source),
+        None if is_wheel => package::extract_wheel(archive, site_packages),
+        None => package::extract_targz(archive, site_packages),
+    }
+}
+
+/// Locked artifacts among `packages` that offline mode cannot install:
+/// neither in the artifact cache or `packages_dir` nor a local file. Source
+/// builds are left to the git and path checks.
+fn missing_offline(packages: &[(PackageName, &LockedPackage)], cache: &Cache, packages_dir: &Path) -> Vec<String> {
+    let mut missing: Vec<String> = packages
+        .iter()
+        .filter(|(_, pkg)| !pkg.is_source_build())
+        .filter_map(|(name, pkg)| {
+            let artifact = select_artifact(&pkg.artifacts, current_platform())?;
+            let available = cache.contains(&artifact.filename, &artifact.sha256)
+                || packages_dir.join(&artifact.filename).exists()
+                || index::local_file(&artifact.url).is_some_and(|path| path.exists());
+            (!available).then(|| format!("{name} {} ({})", pkg.version, artifact.filename))
+        })
+        .collect();
+    missing.sort();
+    missing
+}
+
+/// The bytes of a locked artifact: read from disk for the `file://` URLs
+/// find-links record, downloaded otherwise.
+async fn fetch_artifact(url: &str) -> Result<Vec<u8>, String> {
+    if let Some(path) = index::local_file(url) {
+        return tokio::fs::read(&path)
+            .await
+            .map_err(|e| format!("read of {} failed ({e})", path.display()));
+    }
+    offline::check(|| url.to_string()).map_err(|e| e.to_string())?;
+    let response = crate::core::http::get(url)
+        .send()
+        .await
+        .map_err(|e| format!("request failed ({e})"))?;
+    if !response.status().is_success() {
+        return Err(format!("download failed ({})", response.status()));
+    }
+    let data = response.bytes().await.map_err(|e| format!("download failed ({e})"))?;
+    Ok(data.to_vec())
+}
+
+/// The sha256 of a distribution whose link carried none, as on a flat
+/// find-links page, from a download that is kept in the artifact cache so the
+/// install does not fetch it again.
+async fn hash_unlisted(url: &str, filename: &str) -> Result<String, WovenError> {
+    offline::check(|| url.to_string())?;
+    let data = fetch_artifact(url)
+        .await
+        .map_err(|e| format!("Could not hash {filename} for the lockfile, its link has no sha256: {e}"))?;
+    let sha256 = format!("{:x}", Sha256::digest(&data));
+    match Cache::init() {
+        Ok(cache) => {
+            if let Err(e) = cache.save(filename, &sha256, &data) {
+                log::debug!("could not cache {filename}: {e}");
+            }
+        }
+        Err(e) => log::debug!("could not cache {filename}: {e}"),
+    }
+    Ok(sha256)
+}
+
+/// Rebuilds a git or path package whose wheel is not in the cache. Builds are
+/// not byte-identical, so the locked commit or directory stands in for the
+/// wheel's hash.
+async fn rebuild(url: &str, python_version: &str) -> Result<Vec<u8>, WovenError> {
+    if git::is_git_url(url) {
+        return Ok(git::build(url, python_version).await?.wheel.data);
+    }
+    let path = Path::new(url);
+    if path.is_dir() {
+        Ok(build::build_wheel(python_version, path).await?.data)
+    } else {
+        Ok(tokio::fs::read(path).await?)
+    }
+}
+
+/// Drops the installed `.dist-info` of `name`, so a rebuilt version does not
+/// leave the previous one behind.
+fn remove_dist_info(site_packages: &Path, name: &PackageName) {
+    let Ok(entries) = std::fs::read_dir(site_packages) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if PackageName::from_dist_info(&entry.file_name().to_string_lossy()).as_ref() == Some(name) {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+pub fn prune_unused_packages(
+    site_packages: &Path,
+    lockfile: &Lockfile,
+    selection: &Selection,
+    reporter: &Arc<dyn InstallReporter>,
+) {
     let task = reporter.create_spinner("Pruning environment...");
-    let protected = ["pip", "setuptools", "pkg_resources", "_distutils_hack", "wheel"];
+    let protected = ["pip", "setuptools", "pkg-resources", "distutils-hack", "wheel"];
+    let wanted: HashSet<PackageName> = lockfile
+        .packages_to_install(selection)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
 
     if let Ok(entries) = std::fs::read_dir(site_packages) {
         for entry in entries.flatten() {
             let path = entry.path();
             let name = entry.file_name().to_string_lossy().to_string();
-            let pkg_base_name = if name.ends_with(".dist-info") {
-                name.split('-').next().unwrap_or("").to_lowercase().replace('-', "_")
+            let pkg_base_name = if let Some(dist) = PackageName::from_dist_info(&name) {
+                dist
+            } else if let Some(linked) = name.strip_prefix("__editable__.").and_then(|n| n.strip_suffix(".pth")) {
+                PackageName::new(linked)
             } else if !name.contains('.') && path.is_dir() {
-                name.to_lowercase().replace('-', "_")
+                PackageName::new(&name)
             } else {
                 continue;
             };
             if protected.contains(&pkg_base_name.as_str()) {
                 continue;
             }
-            if !lockfile
-                .packages
-                .keys()
-                .any(|k| k.to_lowercase().replace('-', "_") == pkg_base_name)
-            {
+            if !wanted.contains(&pkg_base_name) {
                 let remove_result = if path.is_dir() {
                     std::fs::remove_dir_all(&path)
                 } else {
@@ -1,23 +1,110 @@
+use pep508_rs::marker::MarkerTree;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
+
+use crate::core::marker;
+use crate::core::name::PackageName;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Lockfile {
     pub name: String,
     pub version: String,
     pub python_version: String,
+    /// Keyed by normalized package name, or `name==version` when a universal
+    /// lock holds several versions of one package for different platforms.
     pub packages: HashMap<String, LockedPackage>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+// The flags mirror independent lockfile fields, not a state machine.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct LockedPackage {
     pub version: String,
     // Support multiple artifacts per version (wheels for different OS/Arch, sdist)
     #[serde(default)]
     pub artifacts: Vec<Artifact>,
     pub dependencies: Vec<String>,
+    /// Extras activated for this package, e.g. `["socks"]` for `requests[socks]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extras: Vec<String>,
+    /// Whether the locked release was yanked when it was resolved (PEP 592).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub yanked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yanked_reason: Option<String>,
+    /// Whether an `overrides` entry forced this package's version range.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overridden: bool,
+    /// PEP 508 marker limiting where this package is installed, e.g.
+    /// `sys_platform != 'win32'`. Only universal locks set it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marker: Option<String>,
+    /// Full commit SHA of a `git+` requirement; the package is reinstalled
+    /// only when this changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Project directory of a path dependency, relative to the project as
+    /// written in `wovenpkg.json`, e.g. `../libs/foo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Whether the path dependency is linked into site-packages with a `.pth` file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub editable: bool,
+    /// Simple API root of the index the release was resolved from. Set for
+    /// every package downloaded from an index, so installs and later checks
+    /// go back to the same index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    /// Dependency groups that need this package, e.g. `["dev", "test"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+    /// Extras of the project itself (`optionalDependencies`) that need this
+    /// package, e.g. `["postgres"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub project_extras: Vec<String>,
+    /// Set when only dependency groups or project extras need this package,
+    /// so a plain production install leaves it out.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+impl LockedPackage {
+    /// Whether the package is built from a git checkout or a local directory
+    /// instead of being downloaded.
+    pub const fn is_source_build(&self) -> bool {
+        self.commit.is_some() || self.path.is_some()
+    }
+}
+
+/// The parts of a lockfile an install puts into the environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// Whether the project's own `dependencies` are installed.
+    pub dependencies: bool,
+    /// Dependency groups installed on top.
+    pub groups: HashSet<String>,
+    /// Project extras installed along with `dependencies`.
+    pub extras: HashSet<String>,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self {
+            dependencies: true,
+            groups: HashSet::new(),
+            extras: HashSet::new(),
+        }
+    }
+}
+
+impl Selection {
+    pub fn includes(&self, package: &LockedPackage) -> bool {
+        let extra = || package.project_extras.iter().any(|e| self.extras.contains(e));
+        (self.dependencies && (!package.optional || extra())) || package.groups.iter().any(|g| self.groups.contains(g))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -44,9 +131,58 @@ impl Lockfile {
         Ok(())
     }
 
+    /// Reads a lockfile, normalizing the keys of ones written with the index's
+    /// display names (`Flask`, `zope.interface`).
     pub fn read(path: &Path) -> Result<Self, crate::core::error::WovenError> {
         let content = fs::read_to_string(path)?;
-        let lockfile: Self = serde_json::from_str(&content)?;
+        let mut lockfile: Self = serde_json::from_str(&content)?;
+        lockfile.packages = lockfile
+            .packages
+            .into_iter()
+            .map(|(key, pkg)| {
+                (
+                    package_key(&package_name(&key), key.split_once("==").map(|(_, v)| v)),
+                    pkg,
+                )
+            })
+            .collect();
         Ok(lockfile)
     }
+
+    /// Entries that apply to the current machine, as `(name, package)`.
+    ///
+    /// Packages without a marker always apply. A marker that cannot be parsed
+    /// or evaluated keeps the package, so a newer lock never silently drops one.
+    pub fn packages_for_current_platform(&self) -> Vec<(PackageName, &LockedPackage)> {
+        let env = marker::build_marker_environment(&self.python_version).ok();
+        self.packages
+            .iter()
+            .filter(|(_, pkg)| {
+                let (Some(env), Some(text)) = (&env, &pkg.marker) else {
+                    return true;
+                };
+                MarkerTree::from_str(text).map_or(true, |tree| tree.evaluate(env, &[]))
+            })
+            .map(|(key, pkg)| (package_name(key), pkg))
+            .collect()
+    }
+
+    /// Entries for the current machine that `selection` asks for.
+    pub fn packages_to_install(&self, selection: &Selection) -> Vec<(PackageName, &LockedPackage)> {
+        self.packages_for_current_platform()
+            .into_iter()
+            .filter(|(_, pkg)| selection.includes(pkg))
+            .collect()
+    }
+}
+
+/// The package name of a lockfile key, dropping a `==version` suffix.
+pub fn package_name(key: &str) -> PackageName {
+    PackageName::new(key.split_once("==").map_or(key, |(name, _)| name))
+}
+
+/// The lockfile key of `name`, with `version` appended for the entries of a
+/// package that a universal lock holds several versions of.
+pub fn package_key(name: &PackageName, version: Option<&str>) -> String {
+    version.map_or_else(|| name.to_string(), |version| format!("{name}=={version}"))
 }
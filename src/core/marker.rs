@@ -1,6 +1,11 @@
-use pep508_rs::marker::{MarkerEnvironment, MarkerEnvironmentBuilder};
-use pep508_rs::{Requirement, VerbatimUrl};
+use pep508_rs::marker::{
+    ExtraOperator, MarkerEnvironment, MarkerEnvironmentBuilder, MarkerExpression, MarkerTree, MarkerValueExtra,
+    MarkerValueVersion,
+};
+use pep508_rs::pep440_rs::Version;
+use pep508_rs::{ExtraName, Requirement, VerbatimUrl};
 use std::env;
+use std::ops::Bound;
 use std::str::FromStr;
 
 /// Build the current environment context for marker evaluation.
@@ -77,6 +82,17 @@ pub fn build_marker_environment(python_version: &str) -> Result<MarkerEnvironmen
 /// Returns `true` if the requirement should be installed, `false` if its
 /// markers exclude it from the current environment.
 pub fn should_include_requirement(requirement_str: &str, marker_env: &MarkerEnvironment) -> bool {
+    should_include_requirement_with_extras(requirement_str, marker_env, &[])
+}
+
+/// Like [`should_include_requirement`], but evaluates `extra == "..."`
+/// markers against the extras requested for the package that declared the
+/// requirement (e.g. `socks` when resolving `requests[socks]`).
+pub fn should_include_requirement_with_extras(
+    requirement_str: &str,
+    marker_env: &MarkerEnvironment,
+    extras: &[ExtraName],
+) -> bool {
     let requirement = match Requirement::<VerbatimUrl>::from_str(requirement_str) {
         Ok(req) => req,
         Err(e) => {
@@ -87,7 +103,68 @@ pub fn should_include_requirement(requirement_str: &str, marker_env: &MarkerEnvi
 
     // evaluate_markers returns true if the requirement should be installed
     // (either no markers, or markers match the environment)
-    requirement.evaluate_markers(marker_env, &[])
+    requirement.evaluate_markers(marker_env, extras)
+}
+
+/// Narrow `marker` to the clauses that still depend on the target machine.
+///
+/// Used for universal locks: `extra` clauses are decided from `extras` and
+/// `python_version` clauses from the project's Python (`3.12`), since both
+/// are fixed for a project. Platform clauses such as `sys_platform` are kept
+/// so they can be evaluated at install time. `python_version` should be the
+/// `major.minor` form.
+pub fn restrict_marker(marker: &MarkerTree, python_version: &Version, extras: &[ExtraName]) -> MarkerTree {
+    if marker.is_true() {
+        return MarkerTree::TRUE;
+    }
+    // Only the minor version is fixed, so patch-level clauses may survive.
+    let release = python_version.release();
+    let next_minor = Version::new([
+        release.first().copied().unwrap_or(3),
+        release.get(1).copied().unwrap_or(0) + 1,
+    ]);
+    let marker = marker
+        .clone()
+        .simplify_python_versions(Bound::Included(python_version), Bound::Excluded(&next_minor));
+    if marker.is_true() || marker.is_false() {
+        return marker;
+    }
+
+    let mut restricted = MarkerTree::FALSE;
+    for clause in marker.to_dnf() {
+        let mut conjunction = MarkerTree::TRUE;
+        for expression in clause {
+            match decide_expression(&expression, python_version, extras) {
+                Some(true) => {}
+                Some(false) => {
+                    conjunction = MarkerTree::FALSE;
+                    break;
+                }
+                None => conjunction.and(MarkerTree::expression(expression)),
+            }
+        }
+        restricted.or(conjunction);
+    }
+    restricted
+}
+
+fn decide_expression(expression: &MarkerExpression, python_version: &Version, extras: &[ExtraName]) -> Option<bool> {
+    match expression {
+        MarkerExpression::Extra { operator, name } => {
+            let active = matches!(name, MarkerValueExtra::Extra(extra) if extras.contains(extra));
+            Some(active == (*operator == ExtraOperator::Equal))
+        }
+        MarkerExpression::Version {
+            key: MarkerValueVersion::PythonVersion,
+            specifier,
+        } => Some(specifier.contains(python_version)),
+        MarkerExpression::VersionIn {
+            key: MarkerValueVersion::PythonVersion,
+            versions,
+            negated,
+        } => Some(versions.contains(python_version) != *negated),
+        _ => None,
+    }
 }
 
 /// Extract the package name from a PEP 508 requirement string.
@@ -156,6 +233,23 @@ mod tests {
         assert!(!should_include_requirement("uvloop; sys_platform=='linux'", &env));
     }
 
+    #[test]
+    fn test_extra_requirement_excluded_without_extra() {
+        let env = build_marker_environment("3.12").unwrap();
+        assert!(!should_include_requirement("PySocks>=1.5.6; extra == 'socks'", &env));
+    }
+
+    #[test]
+    fn test_extra_requirement_included_with_matching_extra() {
+        let env = build_marker_environment("3.12").unwrap();
+        let socks = ExtraName::from_str("socks").unwrap();
+        assert!(should_include_requirement_with_extras(
+            "PySocks>=1.5.6; extra == 'socks'",
+            &env,
+            &[socks]
+        ));
+    }
+
     #[test]
     fn test_should_include_python_version_match() {
         let env = build_marker_environment("3.12").unwrap();
@@ -180,4 +274,25 @@ mod tests {
             assert!(!result);
         }
     }
+
+    #[test]
+    fn test_restrict_marker_keeps_only_platform_clauses() {
+        let py = Version::from_str("3.12").unwrap();
+        let socks = [ExtraName::from_str("socks").unwrap()];
+        let restrict = |m: &str, extras: &[ExtraName]| {
+            restrict_marker(&MarkerTree::from_str(m).unwrap(), &py, extras).try_to_string()
+        };
+
+        assert_eq!(
+            restrict("sys_platform == 'linux' and python_version >= '3.8'", &[]).as_deref(),
+            Some("sys_platform == 'linux'")
+        );
+        assert_eq!(restrict("python_version >= '3.8'", &[]), None);
+        assert!(restrict_marker(&MarkerTree::from_str("python_version < '3.8'").unwrap(), &py, &[]).is_false());
+        assert!(restrict_marker(&MarkerTree::from_str("extra == 'socks'").unwrap(), &py, &[]).is_false());
+        assert_eq!(
+            restrict("extra == 'socks' and os_name == 'nt'", &socks).as_deref(),
+            Some("os_name == 'nt'")
+        );
+    }
 }
@@ -1,12 +1,18 @@
+pub mod auth;
 pub mod cache;
 pub mod config;
 pub mod error;
 pub mod http;
+pub mod http_cache;
 pub mod installer;
 pub mod lock;
 pub mod marker;
+pub mod name;
+pub mod offline;
 pub mod python;
 pub mod python_manager;
 pub mod resolver;
 pub mod selection;
+pub mod settings;
+pub mod timestamp;
 pub mod venv;
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// A package name in PEP 503 normal form: lowercase, with every run of `-`,
+/// `_` and `.` collapsed into a single `-`.
+///
+/// `Zope.Interface`, `zope_interface` and `zope--interface` all name the same
+/// project, so comparing anything but `PackageName`s (lock keys, installed
+/// `.dist-info` directories, `wovenpkg.json` keys) is a bug waiting to happen.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct PackageName(String);
+
+impl PackageName {
+    pub fn new(name: &str) -> Self {
+        let mut normalized = String::with_capacity(name.len());
+        let mut separator = false;
+        for c in name.trim().chars() {
+            if matches!(c, '-' | '_' | '.') {
+                separator = true;
+                continue;
+            }
+            if separator && !normalized.is_empty() {
+                normalized.push('-');
+            }
+            separator = false;
+            normalized.extend(c.to_lowercase());
+        }
+        Self(normalized)
+    }
+
+    /// The project an installed `<name>-<version>.dist-info` directory belongs to.
+    ///
+    /// Wheels escape `-` in the name to `_`, so the first `-` ends the name.
+    pub fn from_dist_info(dir_name: &str) -> Option<Self> {
+        let stem = dir_name.strip_suffix(".dist-info")?;
+        let name = stem.split_once('-').map_or(stem, |(name, _)| name);
+        (!name.is_empty()).then(|| Self::new(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The name as it appears in wheel file and `.dist-info` directory names,
+    /// e.g. `zope_interface`.
+    pub fn as_dist_info_name(&self) -> String {
+        self.0.replace('-', "_")
+    }
+}
+
+impl fmt::Display for PackageName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for PackageName {
+    type Err = std::convert::Infallible;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(name))
+    }
+}
+
+impl From<&str> for PackageName {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<PackageName> for String {
+    fn from(name: PackageName) -> Self {
+        name.0
+    }
+}
+
+impl Deref for PackageName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for PackageName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Hashes like the inner string, so maps keyed by `PackageName` can be
+// queried with an already-normalized `&str`.
+impl Borrow<str> for PackageName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|name| Self::new(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalizes_case_and_separator_runs() {
+        for spelling in [
+            "Zope.Interface",
+            "zope_interface",
+            "zope--interface",
+            "ZOPE._-interface",
+        ] {
+            assert_eq!(PackageName::new(spelling).as_str(), "zope-interface");
+        }
+        assert_eq!(PackageName::new("foo__bar"), PackageName::new("Foo-Bar"));
+        assert_ne!(PackageName::new("foobar"), PackageName::new("foo-bar"));
+        assert_eq!(PackageName::new("zope.interface").as_dist_info_name(), "zope_interface");
+    }
+
+    #[test]
+    fn test_reads_dist_info_directories() {
+        let name = PackageName::from_dist_info("Typing_Extensions-4.12.2.dist-info").unwrap();
+        assert_eq!(name.as_str(), "typing-extensions");
+        assert_eq!(PackageName::from_dist_info("typing_extensions-4.12.2"), None);
+        assert_eq!(PackageName::from_dist_info("-1.0.dist-info"), None);
+    }
+}
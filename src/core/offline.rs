@@ -0,0 +1,37 @@
+//! Offline mode: `--offline` or `WOVEN_OFFLINE=1`.
+//!
+//! Nothing is requested over the network. Index pages and metadata come
+//! from the metadata cache however old they are, packages from the artifact
+//! cache or `findLinks`, git requirements from the local clones and Python
+//! from `PATH` or the managed installs. Anything else fails with
+//! [`WovenError::Offline`], naming what would have had to be downloaded.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::core::error::WovenError;
+
+/// Environment variable that turns offline mode on, like `--offline`.
+pub const OFFLINE_ENV: &str = "WOVEN_OFFLINE";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns offline mode on for the rest of the process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether `--offline` was given or `WOVEN_OFFLINE` is set to anything but
+/// `0`, `false` or nothing.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+        || std::env::var(OFFLINE_ENV)
+            .is_ok_and(|value| !matches!(value.trim().to_ascii_lowercase().as_str(), "" | "0" | "false"))
+}
+
+/// Fails with [`WovenError::Offline`] for `what` in offline mode.
+pub fn check(what: impl FnOnce() -> String) -> Result<(), WovenError> {
+    if is_enabled() {
+        return Err(WovenError::Offline(vec![what()]));
+    }
+    Ok(())
+}
@@ -238,7 +238,7 @@ async fn resolve_python_assets(version: &str) -> Result<Vec<String>, crate::core
         }
     }
 
-    all_assets.sort_by(|a, b| b.0.cmp(&a.0));
+    all_assets.sort_by_key(|a| std::cmp::Reverse(a.0));
 
     let urls: Vec<String> = all_assets.into_iter().map(|(_, url)| url).collect();
 
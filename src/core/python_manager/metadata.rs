@@ -61,7 +61,7 @@ pub fn resolve_from_metadata(
         return Ok(None);
     }
 
-    matches.sort_by(|a, b| b.0.cmp(&a.0));
+    matches.sort_by_key(|a| std::cmp::Reverse(a.0));
     let urls: Vec<String> = matches.into_iter().map(|(_, url)| url).collect();
     println!(
         "  INFO: Using embedded metadata catalog ({} entries) for platform {}.",
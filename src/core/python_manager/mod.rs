@@ -16,6 +16,7 @@ pub async fn ensure_python_version(version: &str) -> Result<PathBuf, crate::core
         return Ok(exe);
     }
 
+    crate::core::offline::check(|| format!("Python {version} (not on PATH or among the managed Pythons)"))?;
     println!("Python {} not found in managed storage. Downloading...", version);
     if !managed_path.exists() {
         fs::create_dir_all(&managed_path)?;
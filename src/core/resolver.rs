@@ -1,124 +1,1472 @@
-use pep508_rs::pep440_rs::{Version, VersionSpecifiers};
-use pep508_rs::{Requirement, VerbatimUrl, VersionOrUrl};
-use std::collections::{HashMap, VecDeque};
+use futures::stream::{self, StreamExt};
+use pep508_rs::marker::{MarkerEnvironment, MarkerTree};
+use pep508_rs::pep440_rs::{Version, VersionSpecifier, VersionSpecifiers};
+use pep508_rs::{ExtraName, Requirement, VerbatimUrl, VersionOrUrl};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::future::Future;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
+use crate::core::auth;
+use crate::core::config::{self, Config, PrereleaseMode, ResolutionStrategy};
+use crate::core::error::WovenError;
 use crate::core::marker;
-use crate::dependencies::package::{self, PackageUrl};
+use crate::core::name::PackageName;
+use crate::core::timestamp::Timestamp;
+use crate::dependencies::index::{self, Index, Indexes};
+use crate::dependencies::package::{self, CandidatePolicy, DirectArtifact, Info, PackageUrl, SkipReason};
+
+/// Every published release of a project, keyed by version string.
+pub type Releases = HashMap<String, Vec<PackageUrl>>;
+
+/// Upper bound on the number of candidate versions the solver evaluates
+/// before giving up. Protects against pathological trees that would
+/// otherwise backtrack for hours.
+const MAX_ATTEMPTS: usize = 10_000;
+
+/// How many dead ends a [`ConflictReport`] keeps; older ones rarely help.
+const MAX_REPORTED_CONFLICTS: usize = 5;
+
+/// Upper bound on the environment slices a universal resolution splits into.
+const MAX_FORKS: usize = 64;
+
+/// Index requests kept in flight while prefetching metadata for queued packages.
+const PREFETCH_CONCURRENCY: usize = 16;
 
 #[derive(Debug, Clone)]
 pub struct ResolutionNode {
     pub name: String,
     pub version: String,
     pub dependencies: Vec<String>,
-    /// Download URLs already fetched during resolution — avoids a second `PyPI` request.
+    /// Extras requested for this package anywhere in the tree, e.g. `socks` for `requests[socks]`.
+    pub extras: Vec<String>,
+    /// Download URLs already fetched during resolution — avoids a second index request.
     pub urls: Vec<PackageUrl>,
+    /// Set when the selected release is yanked; only possible for an exact `==` pin.
+    pub yanked: bool,
+    pub yanked_reason: Option<String>,
+    /// Set when an override replaced every requirement on this package.
+    pub overridden: bool,
+    /// Environments that need this package, for universal resolutions; `None` means all.
+    pub marker: Option<String>,
+    /// Full commit SHA, for packages required from a `git+` URL.
+    pub commit: Option<String>,
+    /// Local project directory, for path dependencies such as `../libs/foo`.
+    pub path: Option<String>,
+    /// Dependency groups that need this package, e.g. `["dev"]`.
+    pub groups: Vec<String>,
+    /// Simple API root of the index the release was found on; `None` for
+    /// direct URLs, git and path dependencies.
+    pub index: Option<String>,
+    /// Extras of the project itself that need this package, e.g. `["postgres"]`.
+    pub project_extras: Vec<String>,
+    /// Set when only dependency groups or project extras need this package,
+    /// not the project's `dependencies`.
+    pub optional: bool,
 }
 
 pub struct DependencyGraph {
+    /// Keyed by normalized name. A universal resolution that picked several
+    /// versions of one package for different environments keys them `name==version`.
     pub packages: HashMap<String, ResolutionNode>,
+    /// Releases newer than the selected ones that were passed over, at most one per package.
+    pub skipped: Vec<SkippedRelease>,
+    /// Packages whose index gives no upload times, so `exclude_newer` could
+    /// not be applied to them. Sorted.
+    pub undated: Vec<String>,
 }
 
-/// Resolves dependencies for the project.
-#[allow(clippy::implicit_hasher)]
-pub async fn resolve(
-    root_deps: &HashMap<String, String>,
-    python_version: &str,
-) -> Result<DependencyGraph, crate::core::error::WovenError> {
-    let mut resolved = HashMap::<String, ResolutionNode>::new();
-    let mut queue = VecDeque::<(String, Option<String>)>::new();
-    let marker_env = marker::build_marker_environment(python_version)?;
-
-    // Start with root dependencies
-    for (name, version_req) in root_deps {
-        queue.push_back((name.clone(), Some(version_req.clone())));
-    }
-
-    while let Some((name, version_constraint)) = queue.pop_front() {
-        let name_lower = name.to_lowercase().replace('-', "_");
-
-        if let Some(existing) = resolved.get(&name_lower) {
-            // Basic conflict detection: if we have a new constraint, check if existing version satisfies it
-            if let Some(constraint_str) = version_constraint {
-                if let Ok(specifiers) = VersionSpecifiers::from_str(&constraint_str) {
-                    if let Ok(version) = Version::from_str(&existing.version) {
-                        if !specifiers.contains(&version) {
-                            return Err(format!(
-                                "Conflict detected for package {}: existing version {} does not satisfy new constraint {}",
-                                name, existing.version, constraint_str
-                            ).into());
-                        }
-                    }
+/// A newer release that matched the version constraints but was ruled out,
+/// e.g. because its `Requires-Python` excludes the project's Python.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRelease {
+    pub package: String,
+    pub version: String,
+    pub reason: SkipReason,
+}
+
+/// Explanation of a failed resolution, detailed enough to fix `wovenpkg.json`
+/// without bisecting by hand.
+#[derive(Debug, Clone)]
+pub struct ConflictReport {
+    /// The package that could not be given any version.
+    pub package: String,
+    /// Dead ends the solver hit, oldest first; the last one is about `package`.
+    pub conflicts: Vec<Conflict>,
+    /// Versions evaluated for each package involved, in the order they were tried.
+    pub tried: Vec<(String, Vec<String>)>,
+}
+
+/// A package for which no version satisfied every requirement at some point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub package: String,
+    pub requirements: Vec<Derivation>,
+    /// Candidates dropped because their own dependencies clashed with earlier picks.
+    pub rejected: Vec<String>,
+}
+
+/// A requirement together with the chain of packages that pulled it in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Derivation {
+    pub requirement: String,
+    /// Path from the project down to the release declaring `requirement`,
+    /// e.g. `["wovenpkg.json", "boto3==1.34.0", "botocore==1.34.0"]`.
+    pub chain: Vec<String>,
+}
+
+impl fmt::Display for ConflictReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no version of {} satisfies every requirement", self.package)
+    }
+}
+
+/// Project-level settings that shape a resolution.
+#[derive(Debug, Clone, Default)]
+pub struct ResolveOptions {
+    /// Python version the project targets, e.g. `3.12`.
+    pub python_version: String,
+    pub prerelease: PrereleaseMode,
+    pub prerelease_packages: Vec<String>,
+    pub resolution: ResolutionStrategy,
+    pub exclude_newer: Option<Timestamp>,
+    /// Resolve for every platform instead of the current machine, keeping
+    /// platform markers so they can be evaluated at install time.
+    pub universal: bool,
+    /// Extra limits on package versions that never add a package, keyed by
+    /// name, e.g. `urllib3` → `<2`.
+    pub constraints: HashMap<String, String>,
+    /// Versions that replace every requirement on a package, including the
+    /// ranges declared by other packages, keyed by name.
+    pub overrides: HashMap<String, String>,
+    /// Named dependency groups such as `dev`, resolved together with the
+    /// project's dependencies so every group agrees on shared versions.
+    pub groups: HashMap<String, HashMap<String, String>>,
+    /// The project's `optionalDependencies`, keyed by extra. All of them are
+    /// locked, so any combination can be installed from one lockfile.
+    pub project_extras: HashMap<String, HashMap<String, String>>,
+    /// Where release listings and metadata are read from.
+    pub indexes: Indexes,
+}
+
+impl ResolveOptions {
+    pub fn new(python_version: &str) -> Self {
+        Self {
+            python_version: python_version.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Options for `config`, reading its constraints and overrides files.
+    pub fn from_config(config: &Config) -> Result<Self, WovenError> {
+        // Installs from the lock download direct URLs again, with the same credentials.
+        for (_, constraint) in config.all_dependencies() {
+            if let Some(url) = config::direct_url(constraint) {
+                auth::register(url);
+            }
+        }
+        Ok(Self {
+            python_version: config.python_version.clone(),
+            prerelease: config.prerelease,
+            prerelease_packages: config.prerelease_packages.clone(),
+            resolution: config.resolution,
+            exclude_newer: config.exclude_newer.clone(),
+            universal: config.universal,
+            constraints: config.constraints()?,
+            overrides: config.overrides()?,
+            groups: config.dependency_groups.clone(),
+            project_extras: config.optional_dependencies.clone(),
+            indexes: Indexes::from_config(config)?,
+        })
+    }
+
+    /// Release filtering rules for [`package::candidate_versions`].
+    pub fn candidate_policy(&self) -> CandidatePolicy {
+        CandidatePolicy {
+            python_version: Version::from_str(&self.python_version).ok(),
+            prerelease: self.prerelease,
+            prerelease_packages: self.prerelease_packages.clone(),
+            exclude_newer: self.exclude_newer.clone(),
+        }
+    }
+}
+
+/// Where the resolver reads release listings and per-version metadata from.
+///
+/// [`IndexSource`] is the production implementation; tests plug in an
+/// in-memory index so the solver can be exercised without the network.
+pub trait PackageSource: Sync {
+    /// All releases of `name` with their downloadable files.
+    fn releases(&self, name: &str) -> impl Future<Output = Result<Arc<Releases>, WovenError>> + Send;
+
+    /// Core metadata (display name, `Requires-Dist`) for one release of `name`.
+    fn metadata(&self, name: &str, version: &str) -> impl Future<Output = Result<Arc<Info>, WovenError>> + Send;
+
+    /// The distribution behind a direct URL requirement, with its metadata.
+    fn direct(&self, url: &str) -> impl Future<Output = Result<Arc<DirectArtifact>, WovenError>> + Send;
+
+    /// URL of the index the releases of `name` were read from, once they have been.
+    fn index_url(&self, _name: &str) -> Option<String> {
+        None
+    }
+}
+
+/// [`PackageSource`] backed by a package index.
+///
+/// Responses are memoised for the lifetime of the source, so backtracking
+/// over a package never triggers a second request for the same document.
+#[derive(Default)]
+pub struct IndexSource {
+    indexes: Indexes,
+    /// Python that `git+` requirements are built with.
+    python_version: String,
+    releases: Mutex<HashMap<String, Arc<Releases>>>,
+    /// The index each package's releases came from; absent for `findLinks` packages.
+    found: Mutex<HashMap<String, Arc<Index>>>,
+    metadata: Mutex<HashMap<(String, String), Arc<Info>>>,
+    direct: Mutex<HashMap<String, Arc<DirectArtifact>>>,
+}
+
+fn memoised<K, V>(map: &Mutex<HashMap<K, Arc<V>>>, key: &K) -> Option<Arc<V>>
+where
+    K: std::hash::Hash + Eq,
+{
+    map.lock().ok()?.get(key).map(Arc::clone)
+}
+
+fn memoise<K, V>(map: &Mutex<HashMap<K, Arc<V>>>, key: K, value: V) -> Arc<V>
+where
+    K: std::hash::Hash + Eq,
+{
+    let value = Arc::new(value);
+    if let Ok(mut guard) = map.lock() {
+        guard.insert(key, Arc::clone(&value));
+    }
+    value
+}
+
+impl IndexSource {
+    pub fn new(indexes: Indexes, python_version: &str) -> Self {
+        Self {
+            indexes,
+            python_version: python_version.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+impl PackageSource for IndexSource {
+    async fn releases(&self, name: &str) -> Result<Arc<Releases>, WovenError> {
+        if let Some(hit) = memoised(&self.releases, &name.to_string()) {
+            return Ok(hit);
+        }
+        let (index, releases) = self.indexes.find(name).await?;
+        if let Some(index) = index {
+            memoise(&self.found, name.to_string(), index);
+        }
+        Ok(memoise(&self.releases, name.to_string(), releases))
+    }
+
+    async fn metadata(&self, name: &str, version: &str) -> Result<Arc<Info>, WovenError> {
+        let key = (name.to_string(), version.to_string());
+        if let Some(hit) = memoised(&self.metadata, &key) {
+            return Ok(hit);
+        }
+        let releases = self.releases(name).await?;
+        let files = releases
+            .get(version)
+            .ok_or_else(|| format!("{name} {version} is not a known release"))?;
+        let info = index::release_metadata(name, version, files).await?;
+        Ok(memoise(&self.metadata, key, info))
+    }
+
+    async fn direct(&self, url: &str) -> Result<Arc<DirectArtifact>, WovenError> {
+        if let Some(hit) = memoised(&self.direct, &url.to_string()) {
+            return Ok(hit);
+        }
+        let artifact = package::fetch_direct_artifact(url, &self.python_version).await?;
+        Ok(memoise(&self.direct, url.to_string(), artifact))
+    }
+
+    fn index_url(&self, name: &str) -> Option<String> {
+        memoised(&self.found, &name.to_string()).map(|index| index.url().to_string())
+    }
+}
+
+/// Who asked for a constraint: the project itself or a specific pinned release.
+#[derive(Debug, Clone)]
+enum Origin {
+    Root,
+    /// A dependency group of `wovenpkg.json`, e.g. `dev`.
+    Group(String),
+    /// An extra of the project's `optionalDependencies`, e.g. `postgres`.
+    Extra(String),
+    /// The `constraints` or `overrides` settings of `wovenpkg.json`.
+    Settings,
+    Package {
+        key: String,
+        display: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Constraint {
+    specifiers: VersionSpecifiers,
+    requirement: String,
+    origin: Origin,
+    /// Set for direct references such as `pkg @ https://host/pkg-1.0-py3-none-any.whl`.
+    url: Option<String>,
+}
+
+impl Constraint {
+    /// Whether `wovenpkg.json` lists the package itself, in `dependencies`,
+    /// a group or an extra.
+    const fn is_direct(&self) -> bool {
+        matches!(self.origin, Origin::Root | Origin::Group(_) | Origin::Extra(_))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Dependency {
+    key: PackageName,
+    name: String,
+    extras: Vec<ExtraName>,
+    specifiers: VersionSpecifiers,
+    requirement: String,
+    url: Option<String>,
+    /// Environments the requirement applies to; always true outside universal mode.
+    marker: MarkerTree,
+}
+
+/// How requirement markers are interpreted.
+enum Markers {
+    /// Evaluated against the machine running woven.
+    Local(MarkerEnvironment),
+    /// Kept for install time. `python` is the project's `major.minor`, and
+    /// `region` is the slice of environments the current fork covers.
+    Universal { python: Version, region: MarkerTree },
+}
+
+/// Result of trying the candidates of one level.
+enum Step {
+    Next(Box<State>),
+    Exhausted,
+    /// A candidate needs a different version of a dependency than itself or
+    /// an earlier pick does, on disjoint environments; resolve each of these
+    /// regions separately.
+    Fork(Vec<MarkerTree>),
+}
+
+enum Outcome {
+    Solved(Box<(State, Search)>),
+    Fork(Vec<MarkerTree>),
+}
+
+#[derive(Debug, Clone)]
+struct Decision {
+    version: Version,
+    version_key: String,
+    dependencies: Vec<Dependency>,
+}
+
+/// The partial solution: every constraint gathered so far plus the versions
+/// already picked. A snapshot is kept per decision so backtracking is a
+/// cheap restore instead of an undo log.
+#[derive(Debug, Clone, Default)]
+struct State {
+    /// Packages in the order they were first required, so decisions are deterministic.
+    order: Vec<String>,
+    names: HashMap<String, String>,
+    /// Virtual `name[extra]` packages → (base package key, extra). Deciding one
+    /// pins the base package to the same version and adds the extra's dependencies.
+    extras: HashMap<String, (String, ExtraName)>,
+    constraints: HashMap<String, Vec<Constraint>>,
+    decided: HashMap<String, Decision>,
+    /// Packages that come from a direct URL instead of the index. The first
+    /// URL seen for a package wins.
+    urls: HashMap<String, String>,
+    /// Packages offline mode could not find, left out so the search can go
+    /// on and list everything else that is missing.
+    unavailable: HashSet<String>,
+    rules: Arc<Rules>,
+}
+
+/// Project-wide constraints and overrides, keyed by normalized name.
+#[derive(Debug, Default)]
+struct Rules {
+    constraints: HashMap<PackageName, Constraint>,
+    overrides: HashMap<PackageName, Constraint>,
+    /// Names of the dependency groups, sorted.
+    groups: Vec<String>,
+    /// Names of the project's extras, sorted.
+    project_extras: Vec<String>,
+}
+
+impl Rules {
+    fn new(options: &ResolveOptions) -> Self {
+        let parse = |entries: &HashMap<String, String>, label: &str| {
+            entries
+                .iter()
+                .map(|(name, spec)| {
+                    let specifiers = root_specifiers(spec);
+                    let constraint = Constraint {
+                        requirement: format!("{name}{specifiers} ({label})"),
+                        specifiers,
+                        origin: Origin::Settings,
+                        url: None,
+                    };
+                    (PackageName::new(name), constraint)
+                })
+                .collect()
+        };
+        Self {
+            constraints: parse(&options.constraints, "constraint"),
+            overrides: parse(&options.overrides, "override"),
+            groups: sorted_keys(&options.groups),
+            project_extras: sorted_keys(&options.project_extras),
+        }
+    }
+}
+
+impl State {
+    fn require(&mut self, key: &str, name: &str, constraint: Constraint) {
+        let base = key.split('[').next().unwrap_or(key);
+        if !self.names.contains_key(key) {
+            self.order.push(key.to_string());
+            self.names.insert(key.to_string(), name.to_string());
+            if let Some(limit) = self.rules.constraints.get(base) {
+                self.constraints.entry(key.to_string()).or_default().push(limit.clone());
+            }
+        }
+        if let Some(url) = &constraint.url {
+            self.urls.entry(key.to_string()).or_insert_with(|| url.clone());
+        }
+        let from = match &constraint.origin {
+            Origin::Package { key, .. } => Some(key.as_str()),
+            _ => None,
+        };
+        let constraint = self.override_for(base, from).cloned().unwrap_or(constraint);
+        self.constraints.entry(key.to_string()).or_default().push(constraint);
+    }
+
+    /// The override standing in for whatever range the package `from`
+    /// declared on `base`, except the pin a `name[extra]` package places on
+    /// its own base.
+    fn override_for(&self, base: &str, from: Option<&str>) -> Option<&Constraint> {
+        let pins_base = from.is_some_and(|from| from.split('[').next() == Some(base));
+        self.rules.overrides.get(base).filter(|_| !pins_base)
+    }
+
+    /// Adds `constraint` for `name` and for the virtual `name[extra]` package
+    /// of every extra requested alongside it.
+    fn require_with_extras(&mut self, key: &str, name: &str, extras: &[ExtraName], constraint: &Constraint) {
+        self.require(key, name, constraint.clone());
+        for extra in extras {
+            let virtual_key = format!("{key}[{extra}]");
+            self.extras
+                .entry(virtual_key.clone())
+                .or_insert_with(|| (key.to_string(), extra.clone()));
+            self.require(&virtual_key, name, constraint.clone());
+        }
+    }
+
+    fn label(&self, key: &str) -> String {
+        match self.extras.get(key) {
+            Some((_, extra)) => format!("{}[{extra}]", self.names[key]),
+            None => self.names[key].clone(),
+        }
+    }
+
+    fn next_undecided(&self) -> Option<String> {
+        self.order
+            .iter()
+            .find(|k| !self.decided.contains_key(*k) && !self.unavailable.contains(*k))
+            .cloned()
+    }
+
+    /// Packages whose pinned versions contributed a constraint on `key`.
+    fn culprits(&self, key: &str) -> HashSet<String> {
+        let mut culprits: HashSet<String> = self
+            .constraints
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter_map(|c| match &c.origin {
+                Origin::Package { key, .. } => Some(key.clone()),
+                Origin::Root | Origin::Group(_) | Origin::Extra(_) | Origin::Settings => None,
+            })
+            .collect();
+        if let Some((base, _)) = self.extras.get(key) {
+            if self.decided.contains_key(base) {
+                culprits.insert(base.clone());
+            }
+        }
+        culprits
+    }
+
+    fn decide(&mut self, key: &str, decision: Decision) {
+        let display = format!("{}=={}", self.label(key), decision.version_key);
+        for dep in &decision.dependencies {
+            self.require_with_extras(
+                &dep.key,
+                &dep.name,
+                &dep.extras,
+                &Constraint {
+                    specifiers: dep.specifiers.clone(),
+                    requirement: dep.requirement.clone(),
+                    origin: Origin::Package {
+                        key: key.to_string(),
+                        display: display.clone(),
+                    },
+                    url: dep.url.clone(),
+                },
+            );
+        }
+        self.decided.insert(key.to_string(), decision);
+    }
+}
+
+/// One decision point of the search: the package being decided, the
+/// alternatives not yet tried, and the packages blamed for failures so far.
+struct Level {
+    key: String,
+    candidates: VecDeque<(Version, String)>,
+    before: State,
+    conflict: HashSet<String>,
+    rejected: Vec<String>,
+}
+
+/// Book-keeping shared by every level of one resolution, used to explain failures.
+#[derive(Default)]
+struct Search {
+    attempts: usize,
+    tried: HashMap<String, Vec<String>>,
+    conflicts: Vec<Conflict>,
+    skipped: HashMap<String, Vec<(Version, SkipReason)>>,
+    /// Packages whose metadata has already been requested ahead of time.
+    prefetched: HashSet<String>,
+    /// Packages listed without any upload time while `exclude_newer` is set.
+    undated: HashSet<String>,
+    /// Pages and files offline mode could not find.
+    offline: Vec<String>,
+}
+
+impl Search {
+    /// Everything offline mode was missing, once the search is over.
+    fn offline_error(&self) -> Option<WovenError> {
+        if self.offline.is_empty() {
+            return None;
+        }
+        let mut missing = self.offline.clone();
+        missing.sort();
+        missing.dedup();
+        Some(WovenError::Offline(missing))
+    }
+
+    fn record(&mut self, level: &Level) {
+        let conflict = Conflict {
+            package: level.before.label(&level.key),
+            requirements: level
+                .before
+                .constraints
+                .get(&level.key)
+                .into_iter()
+                .flatten()
+                .map(|c| derivation(&level.before, c))
+                .collect(),
+            rejected: level.rejected.clone(),
+        };
+        if self.conflicts.last() != Some(&conflict) {
+            self.conflicts.push(conflict);
+        }
+        if self.conflicts.len() > MAX_REPORTED_CONFLICTS {
+            self.conflicts.remove(0);
+        }
+    }
+
+    fn report(&self, level: &Level) -> ConflictReport {
+        let involved: HashSet<&str> = self.conflicts.iter().map(|c| c.package.as_str()).collect();
+        let mut tried: Vec<(String, Vec<String>)> = self
+            .tried
+            .iter()
+            .filter(|(name, versions)| versions.len() > 1 || involved.contains(name.as_str()))
+            .map(|(name, versions)| (name.clone(), versions.clone()))
+            .collect();
+        tried.sort();
+
+        ConflictReport {
+            package: level.before.label(&level.key),
+            conflicts: self.conflicts.clone(),
+            tried,
+        }
+    }
+}
+
+/// Walks a constraint back to `wovenpkg.json` through the constraints that
+/// first introduced each intermediate package.
+fn derivation(state: &State, constraint: &Constraint) -> Derivation {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::new();
+    let mut origin = &constraint.origin;
+    while let Origin::Package { key, display } = origin {
+        if !seen.insert(key.as_str()) {
+            break;
+        }
+        chain.push(display.clone());
+        match state.constraints.get(key).and_then(|c| c.first()) {
+            Some(parent) => origin = &parent.origin,
+            None => break,
+        }
+    }
+    chain.push(match origin {
+        Origin::Group(group) => format!("wovenpkg.json ({group} group)"),
+        Origin::Extra(extra) => format!("wovenpkg.json ({extra} extra)"),
+        _ => "wovenpkg.json".to_string(),
+    });
+    chain.reverse();
+    Derivation {
+        requirement: constraint.requirement.clone(),
+        chain,
+    }
+}
+
+/// Interprets a `wovenpkg.json` constraint: empty means "any version", a
+/// bare version like `2.25.1` means an exact pin, anything else is parsed as
+/// PEP 440 specifiers. Malformed specifiers fall back to "any version".
+fn root_specifiers(constraint: &str) -> VersionSpecifiers {
+    let constraint = constraint.trim();
+    if constraint.is_empty() || constraint == "*" {
+        return VersionSpecifiers::empty();
+    }
+    let constraint = if constraint.chars().next().is_some_and(|c| c.is_ascii_digit()) {
+        format!("=={constraint}")
+    } else {
+        constraint.to_string()
+    };
+    VersionSpecifiers::from_str(&constraint).unwrap_or_else(|_| VersionSpecifiers::empty())
+}
+
+/// Splits a `wovenpkg.json` key such as `requests[socks]` into the project
+/// name and its requested extras.
+fn split_extras(key: &str) -> (String, Vec<ExtraName>) {
+    match Requirement::<VerbatimUrl>::from_str(key) {
+        Ok(req) if !req.extras.is_empty() => (key[..key.find('[').unwrap_or(key.len())].trim().to_string(), req.extras),
+        _ => (key.to_string(), Vec::new()),
+    }
+}
+
+/// Requirements of a release that apply to this environment, or in
+/// universal mode to any environment of the current fork. With `extra` set,
+/// only the requirements that the extra adds on top of the base package are
+/// returned.
+fn parse_dependencies(
+    info: &Info,
+    markers: &Markers,
+    extra: Option<&ExtraName>,
+) -> Result<Vec<Dependency>, WovenError> {
+    let mut dependencies = Vec::new();
+    for req_str in info.requires_dist.iter().flatten() {
+        let marker = match markers {
+            Markers::Local(marker_env) => {
+                let include = extra.map_or_else(
+                    || marker::should_include_requirement(req_str, marker_env),
+                    |extra| {
+                        marker::should_include_requirement_with_extras(req_str, marker_env, std::slice::from_ref(extra))
+                            && !marker::should_include_requirement(req_str, marker_env)
+                    },
+                );
+                if !include {
+                    continue;
+                }
+                MarkerTree::TRUE
+            }
+            Markers::Universal { python, region } => {
+                let req = Requirement::<VerbatimUrl>::from_str(req_str)?;
+                let mut marker =
+                    marker::restrict_marker(&req.marker, python, extra.map(std::slice::from_ref).unwrap_or_default());
+                if extra.is_some() {
+                    marker.and(marker::restrict_marker(&req.marker, python, &[]).negate());
                 }
+                if marker.is_false() || marker.is_disjoint(region) {
+                    continue;
+                }
+                marker
+            }
+        };
+        let req = Requirement::<VerbatimUrl>::from_str(req_str)?;
+        let (specifiers, url) = match req.version_or_url {
+            Some(VersionOrUrl::VersionSpecifier(spec)) => (spec, None),
+            Some(VersionOrUrl::Url(url)) => (VersionSpecifiers::empty(), Some(url.to_string())),
+            None => (VersionSpecifiers::empty(), None),
+        };
+        let name = req.name.to_string();
+        dependencies.push(Dependency {
+            key: PackageName::new(&name),
+            name,
+            extras: req.extras,
+            specifiers,
+            requirement: req_str.clone(),
+            url,
+            marker,
+        });
+    }
+    Ok(dependencies)
+}
+
+/// Regions to split `region` into when `dependencies`, together with the
+/// requirements of the packages already decided in `state`, ask for
+/// different versions of the same package under pairwise disjoint markers,
+/// e.g. `numpy>=2; sys_platform == 'linux'` next to `numpy<2; sys_platform == 'win32'`.
+fn fork_regions(dependencies: &[Dependency], state: &State, region: &MarkerTree) -> Option<Vec<MarkerTree>> {
+    let decided: Vec<&Dependency> = state
+        .order
+        .iter()
+        .filter_map(|key| state.decided.get(key))
+        .flat_map(|decision| &decision.dependencies)
+        .collect();
+    for (i, dep) in dependencies.iter().enumerate() {
+        // Requirements asking for the same versions count as one, under any of their markers.
+        let mut ranges: Vec<(&VersionSpecifiers, MarkerTree)> = Vec::new();
+        for sibling in dependencies[i..].iter().chain(decided.iter().copied()) {
+            if sibling.key != dep.key {
+                continue;
+            }
+            match ranges
+                .iter_mut()
+                .find(|(specifiers, _)| **specifiers == sibling.specifiers)
+            {
+                Some((_, marker)) => marker.or(sibling.marker.clone()),
+                None => ranges.push((&sibling.specifiers, sibling.marker.clone())),
+            }
+        }
+        if ranges.len() < 2 {
+            continue;
+        }
+        let disjoint = ranges
+            .iter()
+            .enumerate()
+            .all(|(a, (_, x))| ranges[a + 1..].iter().all(|(_, y)| x.is_disjoint(y)));
+        if !disjoint {
+            continue;
+        }
+
+        let mut rest = region.clone();
+        let mut regions = Vec::new();
+        for (_, marker) in ranges {
+            let mut slice = region.clone();
+            slice.and(marker.clone());
+            regions.push(slice);
+            rest.and(marker.negate());
+        }
+        if !rest.is_false() {
+            regions.push(rest);
+        }
+        return Some(regions);
+    }
+    None
+}
+
+/// Environments in which each decided package is reachable from the project,
+/// starting from `region` at the roots.
+fn package_markers(state: &State, region: &MarkerTree) -> HashMap<String, MarkerTree> {
+    let mut markers: HashMap<String, MarkerTree> = state
+        .constraints
+        .iter()
+        .filter(|(_, constraints)| constraints.iter().any(Constraint::is_direct))
+        .map(|(key, _)| (key.clone(), region.clone()))
+        .collect();
+
+    loop {
+        let mut changed = false;
+        for (key, decision) in &state.decided {
+            let Some(parent) = markers.get(key).cloned() else {
+                continue;
+            };
+            for dep in &decision.dependencies {
+                let mut reach = parent.clone();
+                reach.and(dep.marker.clone());
+                let current = markers.entry(dep.key.to_string()).or_insert(MarkerTree::FALSE);
+                let mut merged = current.clone();
+                merged.or(reach);
+                if merged != *current {
+                    *current = merged;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return markers;
+        }
+    }
+}
+
+/// Versions of `key` allowed by every constraint gathered so far, in the
+/// order `strategy` prefers them.
+///
+/// Filtering follows [`package::candidate_versions`]; releases it rules out
+/// are remembered in `search` so newer skipped versions can be reported when
+/// the package is resolved highest-first. A `name[extra]` package whose base
+/// is already decided only offers that same version.
+async fn candidates_for<S: PackageSource>(
+    source: &S,
+    state: &State,
+    key: &str,
+    policy: &CandidatePolicy,
+    strategy: ResolutionStrategy,
+    search: &mut Search,
+) -> Result<VecDeque<(Version, String)>, WovenError> {
+    let releases = releases_for(source, state, key).await?;
+    let indexed = !state.urls.contains_key(key) && !state.extras.contains_key(key);
+    if policy.exclude_newer.is_some()
+        && indexed
+        && releases
+            .values()
+            .flatten()
+            .all(|file| file.upload_time_iso_8601.is_none())
+    {
+        search.undated.insert(key.to_string());
+    }
+    let specifiers = combined_specifiers(state, key);
+    let base = state.extras.get(key).and_then(|(base, _)| state.decided.get(base));
+
+    let project = state.extras.get(key).map_or(key, |(base, _)| base.as_str());
+    let mut candidates = package::candidate_versions(project, &releases, &specifiers, policy);
+    candidates
+        .versions
+        .retain(|(v, _)| base.is_none_or(|b| &b.version == v));
+    if prefers_lowest(state, key, strategy) {
+        candidates.versions.reverse();
+    } else if !candidates.skipped.is_empty() {
+        search.skipped.insert(key.to_string(), candidates.skipped);
+    }
+    Ok(candidates.versions.into())
+}
+
+/// Release listing for `key`: the index's, or the single file of its direct URL.
+async fn releases_for<S: PackageSource>(source: &S, state: &State, key: &str) -> Result<Arc<Releases>, WovenError> {
+    match state.urls.get(key) {
+        Some(url) => {
+            let artifact = source.direct(url).await?;
+            Ok(Arc::new(HashMap::from([(
+                artifact.info.version.clone(),
+                vec![artifact.file.clone()],
+            )])))
+        }
+        None => source.releases(&state.names[key]).await,
+    }
+}
+
+async fn metadata_for<S: PackageSource>(
+    source: &S,
+    state: &State,
+    key: &str,
+    version_key: &str,
+) -> Result<Arc<Info>, WovenError> {
+    match state.urls.get(key) {
+        Some(url) => Ok(Arc::new(source.direct(url).await?.info.clone())),
+        None => source.metadata(&state.names[key], version_key).await,
+    }
+}
+
+/// Every specifier gathered for `key` so far.
+fn combined_specifiers(state: &State, key: &str) -> VersionSpecifiers {
+    state
+        .constraints
+        .get(key)
+        .into_iter()
+        .flatten()
+        .flat_map(|c| c.specifiers.iter().cloned())
+        .collect()
+}
+
+fn prefers_lowest(state: &State, key: &str, strategy: ResolutionStrategy) -> bool {
+    match strategy {
+        ResolutionStrategy::Highest => false,
+        ResolutionStrategy::Lowest => true,
+        ResolutionStrategy::LowestDirect => state
+            .constraints
+            .get(key)
+            .is_some_and(|c| c.iter().any(Constraint::is_direct)),
+    }
+}
+
+/// Warms `source` for every package queued but not yet decided: the release
+/// listing and the metadata of the version the solver will most likely try
+/// first are requested concurrently, so the serial search below mostly hits
+/// the source's memo instead of waiting on one round trip per package.
+///
+/// Failures are ignored here; they surface when the solver asks for the same
+/// document itself.
+async fn prefetch<S: PackageSource>(
+    source: &S,
+    state: &State,
+    policy: &CandidatePolicy,
+    strategy: ResolutionStrategy,
+    search: &mut Search,
+) {
+    let queued: Vec<(&str, &str, VersionSpecifiers, bool)> = state
+        .order
+        .iter()
+        .filter(|key| {
+            !state.decided.contains_key(*key) && !state.extras.contains_key(*key) && !state.urls.contains_key(*key)
+        })
+        .filter(|key| search.prefetched.insert((*key).clone()))
+        .map(|key| {
+            (
+                key.as_str(),
+                state.names[key].as_str(),
+                combined_specifiers(state, key),
+                prefers_lowest(state, key, strategy),
+            )
+        })
+        .collect();
+    if queued.len() < 2 {
+        return;
+    }
+
+    stream::iter(queued)
+        .for_each_concurrent(PREFETCH_CONCURRENCY, |(key, name, specifiers, lowest)| async move {
+            let Ok(releases) = source.releases(name).await else {
+                return;
+            };
+            let candidates = package::candidate_versions(key, &releases, &specifiers, policy).versions;
+            let likely = if lowest { candidates.last() } else { candidates.first() };
+            if let Some((_, version_key)) = likely {
+                let _ = source.metadata(name, version_key).await;
             }
+        })
+        .await;
+}
+
+/// Tries the remaining candidates of `level` in order and returns the state
+/// after the first one whose dependencies agree with the versions already
+/// picked. Rejected candidates add the clashing package to the level's
+/// conflict set so a later backjump knows whom to blame.
+async fn advance<S: PackageSource>(
+    source: &S,
+    level: &mut Level,
+    markers: &Markers,
+    search: &mut Search,
+) -> Result<Step, WovenError> {
+    let name = level.before.names[&level.key].clone();
+
+    while let Some((version, version_key)) = level.candidates.pop_front() {
+        search.attempts += 1;
+        if search.attempts > MAX_ATTEMPTS {
+            return Err(WovenError::ResolutionConflict(format!(
+                "gave up after evaluating {MAX_ATTEMPTS} candidate versions"
+            )));
+        }
+
+        let tried = search.tried.entry(level.before.label(&level.key)).or_default();
+        if !tried.contains(&version_key) {
+            tried.push(version_key.clone());
+        }
+
+        let info = match metadata_for(source, &level.before, &level.key, &version_key).await {
+            Err(WovenError::Offline(missing)) => {
+                search.offline.extend(missing);
+                let mut next = level.before.clone();
+                next.unavailable.insert(level.key.clone());
+                return Ok(Step::Next(Box::new(next)));
+            }
+            result => result?,
+        };
+        let extra = level.before.extras.get(&level.key).cloned();
+        let mut dependencies = parse_dependencies(&info, markers, extra.as_ref().map(|(_, e)| e))?;
+        if let Markers::Universal { region, .. } = markers {
+            if let Some(regions) = fork_regions(&dependencies, &level.before, region) {
+                return Ok(Step::Fork(regions));
+            }
+        }
+        if let Some((base, _)) = extra {
+            dependencies.insert(
+                0,
+                Dependency {
+                    key: PackageName::new(&base),
+                    name: name.clone(),
+                    extras: Vec::new(),
+                    specifiers: VersionSpecifier::equals_version(version.clone()).into(),
+                    requirement: format!("{name}=={version_key}"),
+                    url: None,
+                    marker: MarkerTree::TRUE,
+                },
+            );
+        }
+
+        let clash = dependencies.iter().find(|dep| {
+            let specifiers = level
+                .before
+                .override_for(dep.key.as_str(), Some(&level.key))
+                .map_or(&dep.specifiers, |forced| &forced.specifiers);
+            level
+                .before
+                .decided
+                .get(dep.key.as_str())
+                .is_some_and(|picked| !specifiers.contains(&picked.version))
+        });
+        if let Some(dep) = clash {
+            level.rejected.push(format!(
+                "{} {version_key} requires {}, but {} {} is already selected",
+                level.before.label(&level.key),
+                dep.requirement,
+                level.before.names[dep.key.as_str()],
+                level.before.decided[dep.key.as_str()].version_key
+            ));
+            level.conflict.insert(dep.key.to_string());
             continue;
         }
 
-        // Determine which version to fetch from PyPI:
-        //   1. Exact specifier (==x.y.z)  → fetch the versioned endpoint directly.
-        //   2. Range/inequality specifier  → enumerate all releases, select the
-        //      highest version that satisfies all constraints (PEP440), then fetch
-        //      that specific version.
-        //   3. No constraint               → fetch the latest release endpoint.
-        let fetch_version: Option<String> = match version_constraint.as_deref() {
-            None | Some("") => None,
-            Some(constraint_str) => {
-                // Try to interpret as a bare exact version like "1.2.3"
-                let trimmed = constraint_str.trim_start_matches("==");
-                if trimmed.chars().next().is_some_and(|c| c.is_ascii_digit()) {
-                    Some(trimmed.to_string())
-                } else {
-                    // Range / inequality specifier: find the best satisfying version.
-                    match VersionSpecifiers::from_str(constraint_str) {
-                        Ok(specifiers) => {
-                            let full = package::fetch_full_package_info(&name).await?;
-                            match package::select_best_candidate(&full.releases, &specifiers) {
-                                Some(best) => Some(best),
-                                None => {
-                                    return Err(format!(
-                                        "No version of '{name}' satisfies constraint '{constraint_str}'"
-                                    )
-                                    .into())
-                                }
-                            }
-                        }
-                        Err(_) => None, // malformed specifier → fall back to latest
+        let mut next = level.before.clone();
+        next.decide(
+            &level.key,
+            Decision {
+                version,
+                version_key,
+                dependencies,
+            },
+        );
+        return Ok(Step::Next(Box::new(next)));
+    }
+
+    Ok(Step::Exhausted)
+}
+
+/// Conflict-directed backjump: discards decisions back to the most recent
+/// one blamed for `exhausted` running out of candidates, carrying the blame
+/// over so that level can in turn jump further if it also runs dry.
+fn backjump(levels: &mut Vec<Level>, exhausted: &Level, search: &Search) -> Result<Level, WovenError> {
+    let mut conflict = exhausted.conflict.clone();
+    conflict.remove(&exhausted.key);
+
+    let Some(pos) = levels.iter().rposition(|l| conflict.contains(&l.key)) else {
+        return Err(WovenError::NoSolution(Box::new(search.report(exhausted))));
+    };
+    levels.truncate(pos + 1);
+    let mut target = levels.pop().ok_or("resolver lost its decision stack")?;
+    conflict.remove(&target.key);
+    target.conflict.extend(conflict);
+    Ok(target)
+}
+
+/// Resolves dependencies for the project.
+pub async fn resolve(config: &Config) -> Result<DependencyGraph, crate::core::error::WovenError> {
+    let options = ResolveOptions::from_config(config)?;
+    resolve_with(
+        &IndexSource::new(options.indexes.clone(), &config.python_version),
+        &config.dependencies,
+        &options,
+    )
+    .await
+}
+
+/// Resolves `root_deps` against `source` with a backtracking search.
+///
+/// Packages are decided one at a time, trying acceptable versions in the
+/// order `options.resolution` prefers (highest first by default).
+/// When a package has no version left that satisfies every constraint, the
+/// solver jumps back to the most recent decision that contributed to the
+/// conflict and tries that package's next candidate, so earlier picks can be
+/// downgraded instead of failing on the first disagreement.
+///
+/// With `options.universal`, markers are kept rather than evaluated. When a
+/// package needs different versions of a dependency on disjoint platforms,
+/// the resolution forks and each slice of environments is solved on its own;
+/// the results are merged and every node carries the marker it applies under.
+#[allow(clippy::implicit_hasher)]
+pub async fn resolve_with<S: PackageSource>(
+    source: &S,
+    root_deps: &HashMap<String, String>,
+    options: &ResolveOptions,
+) -> Result<DependencyGraph, WovenError> {
+    let marker_env = marker::build_marker_environment(&options.python_version)?;
+    let policy = options.candidate_policy();
+    let python = Version::from_str(&options.python_version)?.only_release();
+    let python = Version::new(python.release().iter().take(2));
+
+    let mut pending = vec![MarkerTree::TRUE];
+    let mut forks = 1;
+    let mut nodes: Vec<(String, ResolutionNode, MarkerTree)> = Vec::new();
+    let mut skipped: Vec<SkippedRelease> = Vec::new();
+    let mut undated = BTreeSet::new();
+
+    while let Some(region) = pending.pop() {
+        let markers = if options.universal {
+            Markers::Universal {
+                python: python.clone(),
+                region: region.clone(),
+            }
+        } else {
+            Markers::Local(marker_env.clone())
+        };
+
+        match solve(source, root_deps, options, &policy, &markers).await? {
+            Outcome::Fork(regions) => {
+                forks += regions.len() - 1;
+                if forks > MAX_FORKS {
+                    return Err(WovenError::ResolutionConflict(format!(
+                        "universal resolution split into more than {MAX_FORKS} platform forks"
+                    )));
+                }
+                pending.extend(regions.into_iter().rev());
+            }
+            Outcome::Solved(solved) => {
+                let (state, search) = *solved;
+                let reach = options.universal.then(|| package_markers(&state, &region));
+                for release in collect_skipped(&state, &search) {
+                    if !skipped.iter().any(|s| s.package == release.package) {
+                        skipped.push(release);
+                    }
+                }
+                undated.extend(
+                    search
+                        .undated
+                        .iter()
+                        .filter(|key| state.decided.contains_key(*key))
+                        .map(|key| state.names[key].clone()),
+                );
+                for (key, node, marker) in build_nodes(source, state, &policy, reach.as_ref()).await? {
+                    match nodes
+                        .iter_mut()
+                        .find(|(k, n, _)| *k == key && n.version == node.version)
+                    {
+                        Some((_, existing, existing_marker)) => merge_node(existing, existing_marker, node, marker),
+                        None => nodes.push((key, node, marker)),
                     }
                 }
             }
+        }
+    }
+
+    let mut versions: HashMap<String, usize> = HashMap::new();
+    for (key, _, _) in &nodes {
+        *versions.entry(key.clone()).or_default() += 1;
+    }
+    let mut packages = HashMap::new();
+    for (key, mut node, marker) in nodes {
+        node.marker = marker.try_to_string();
+        // Several versions picked for different platforms are keyed by version.
+        let key = if versions[&key] > 1 {
+            format!("{key}=={}", node.version)
+        } else {
+            key
         };
+        packages.insert(key, node);
+    }
 
-        let info = package::fetch_package_info(&name, fetch_version.as_deref()).await?;
-        // The info object is consumed directly below
-        let mut sub_deps = Vec::new();
-
-        if let Some(requires_dist) = info.info.requires_dist {
-            for req_str in requires_dist {
-                // Filter by markers
-                if marker::should_include_requirement(&req_str, &marker_env) {
-                    let req = Requirement::<VerbatimUrl>::from_str(&req_str)?;
-                    let sub_name = req.name.to_string();
-                    let sub_constraint = match req.version_or_url {
-                        Some(VersionOrUrl::VersionSpecifier(spec)) => Some(spec.to_string()),
-                        _ => None,
-                    };
+    skipped.sort_by(|a, b| a.package.cmp(&b.package));
+    Ok(DependencyGraph {
+        packages,
+        skipped,
+        undated: undated.into_iter().collect(),
+    })
+}
 
-                    sub_deps.push(sub_name.clone());
-                    queue.push_back((sub_name, sub_constraint));
+/// Runs the backtracking search once, for one set of marker rules.
+async fn solve<S: PackageSource>(
+    source: &S,
+    root_deps: &HashMap<String, String>,
+    options: &ResolveOptions,
+    policy: &CandidatePolicy,
+    markers: &Markers,
+) -> Result<Outcome, WovenError> {
+    let mut roots: Vec<_> = root_deps.iter().collect();
+    roots.sort();
+
+    let mut state = State {
+        rules: Arc::new(Rules::new(options)),
+        ..State::default()
+    };
+    let grouped = named_roots(&options.groups, Origin::Group);
+    let extras = named_roots(&options.project_extras, Origin::Extra);
+    for (name, constraint, origin) in roots
+        .into_iter()
+        .map(|(name, constraint)| (name, constraint, Origin::Root))
+        .chain(grouped)
+        .chain(extras)
+    {
+        let (project, extras) = split_extras(name);
+        let url = config::direct_url(constraint).or_else(|| config::local_path(constraint).map(|local| local.path));
+        let specifiers = if url.is_some() {
+            VersionSpecifiers::empty()
+        } else {
+            root_specifiers(constraint)
+        };
+        let root = Constraint {
+            requirement: url.map_or_else(
+                || format!("{name}{specifiers}"),
+                |url| format!("{name} @ {}", auth::redact(url)),
+            ),
+            specifiers,
+            origin,
+            url: url.map(ToString::to_string),
+        };
+        state.require_with_extras(&PackageName::new(&project), &project, &extras, &root);
+    }
+
+    let mut levels: Vec<Level> = Vec::new();
+    let mut search = Search::default();
+
+    while let Some(key) = state.next_undecided() {
+        prefetch(source, &state, policy, options.resolution, &mut search).await;
+        let candidates = match candidates_for(source, &state, &key, policy, options.resolution, &mut search).await {
+            Err(WovenError::Offline(missing)) => {
+                search.offline.extend(missing);
+                state.unavailable.insert(key);
+                continue;
+            }
+            result => result?,
+        };
+        let mut level = Level {
+            conflict: state.culprits(&key),
+            key,
+            candidates,
+            before: state.clone(),
+            rejected: Vec::new(),
+        };
+
+        loop {
+            match advance(source, &mut level, markers, &mut search).await? {
+                Step::Next(next) => {
+                    state = *next;
+                    levels.push(level);
+                    break;
+                }
+                Step::Fork(regions) => return Ok(Outcome::Fork(regions)),
+                Step::Exhausted => {
+                    search.record(&level);
+                    level = backjump(&mut levels, &level, &search).map_err(|e| search.offline_error().unwrap_or(e))?;
                 }
             }
         }
+    }
 
-        resolved.insert(
-            name_lower,
+    if let Some(error) = search.offline_error() {
+        return Err(error);
+    }
+    Ok(Outcome::Solved(Box::new((state, search))))
+}
+
+/// The newest release skipped above each decided package, if any.
+fn collect_skipped(state: &State, search: &Search) -> Vec<SkippedRelease> {
+    let mut skipped = Vec::new();
+    for (key, decision) in &state.decided {
+        if state.extras.contains_key(key) {
+            continue;
+        }
+        if let Some((version, reason)) = search
+            .skipped
+            .get(key)
+            .and_then(|s| s.first())
+            .filter(|(v, _)| v > &decision.version)
+        {
+            skipped.push(SkippedRelease {
+                package: state.names[key].clone(),
+                version: version.to_string(),
+                reason: reason.clone(),
+            });
+        }
+    }
+    skipped
+}
+
+/// Turns a solved state into graph nodes, folding every `name[extra]` back
+/// into its base package. With `reach` set (universal mode), each node gets
+/// the marker it is needed under and unreachable ones are dropped.
+async fn build_nodes<S: PackageSource>(
+    source: &S,
+    mut state: State,
+    policy: &CandidatePolicy,
+    reach: Option<&HashMap<String, MarkerTree>>,
+) -> Result<Vec<(String, ResolutionNode, MarkerTree)>, WovenError> {
+    let mut extras: HashMap<String, (Vec<String>, Vec<String>)> = HashMap::new();
+    for (key, decision) in &state.decided {
+        if let Some((base, extra)) = state.extras.get(key) {
+            let (names, deps) = extras.entry(base.clone()).or_default();
+            names.push(extra.to_string());
+            deps.extend(decision.dependencies.iter().skip(1).map(|d| d.name.clone()));
+        }
+    }
+
+    let main = reachable(&state, |origin| matches!(origin, Origin::Root));
+    let group_reach: Vec<(&String, HashSet<String>)> = state
+        .rules
+        .groups
+        .iter()
+        .map(|group| {
+            (
+                group,
+                reachable(&state, |origin| matches!(origin, Origin::Group(g) if g == group)),
+            )
+        })
+        .collect();
+    let extra_reach: Vec<(&String, HashSet<String>)> = state
+        .rules
+        .project_extras
+        .iter()
+        .map(|extra| {
+            (
+                extra,
+                reachable(&state, |origin| matches!(origin, Origin::Extra(e) if e == extra)),
+            )
+        })
+        .collect();
+
+    let mut nodes = Vec::new();
+    let decided = std::mem::take(&mut state.decided);
+    for (key, decision) in decided {
+        if state.extras.contains_key(&key) {
+            continue;
+        }
+        let marker = reach.map_or(MarkerTree::TRUE, |reach| {
+            reach.get(&key).cloned().unwrap_or(MarkerTree::FALSE)
+        });
+        if marker.is_false() {
+            continue;
+        }
+        let overridden = state.rules.overrides.contains_key(key.as_str());
+        let commit = match state.urls.get(&key) {
+            Some(url) => source.direct(url).await?.commit.clone(),
+            None => None,
+        };
+        let path = state
+            .urls
+            .get(&key)
+            .and_then(|url| config::local_path(url))
+            .map(|local| local.path.to_string());
+        let needing = |reaches: &[(&String, HashSet<String>)]| -> Vec<String> {
+            reaches
+                .iter()
+                .filter(|(_, reach)| reach.contains(&key))
+                .map(|(name, _)| (*name).clone())
+                .collect()
+        };
+        let groups = needing(&group_reach);
+        let project_extras = needing(&extra_reach);
+        let optional = !main.contains(&key);
+        let info = metadata_for(source, &state, &key, &decision.version_key).await?;
+        let releases = releases_for(source, &state, &key).await?;
+        let index = if state.urls.contains_key(&key) {
+            None
+        } else {
+            source.index_url(&state.names[&key])
+        };
+        let mut urls: Vec<PackageUrl> = releases
+            .get(&decision.version_key)
+            .into_iter()
+            .flatten()
+            .filter(|u| policy.admits(u))
+            .cloned()
+            .collect();
+        let yanked = package::release_yanked(&urls);
+        let yanked_reason = if yanked { package::yanked_reason(&urls) } else { None };
+        if !yanked {
+            urls.retain(|u| !u.yanked);
+        }
+        let (mut extra_names, extra_deps) = extras.remove(&key).unwrap_or_default();
+        extra_names.sort();
+        let mut dependencies: Vec<String> = decision.dependencies.into_iter().map(|d| d.name).collect();
+        for dep in extra_deps {
+            if !dependencies.contains(&dep) {
+                dependencies.push(dep);
+            }
+        }
+        nodes.push((
+            key,
             ResolutionNode {
-                name: info.info.name,
-                version: info.info.version,
-                dependencies: sub_deps,
-                urls: info.urls,
+                name: info.name.clone(),
+                version: info.version.clone(),
+                dependencies,
+                extras: extra_names,
+                urls,
+                yanked_reason,
+                yanked,
+                overridden,
+                marker: None,
+                commit,
+                path,
+                groups,
+                index,
+                project_extras,
+                optional,
             },
-        );
+            marker,
+        ));
+    }
+    Ok(nodes)
+}
+
+/// The requirements of named sets such as dependency groups, in name order,
+/// each tagged with the origin `origin` builds from its set's name.
+fn named_roots(
+    sets: &HashMap<String, HashMap<String, String>>,
+    origin: fn(String) -> Origin,
+) -> impl Iterator<Item = (&String, &String, Origin)> {
+    let mut sets: Vec<_> = sets.iter().collect();
+    sets.sort_by_key(|(name, _)| *name);
+    sets.into_iter().flat_map(move |(set, deps)| {
+        let mut deps: Vec<_> = deps.iter().collect();
+        deps.sort();
+        deps.into_iter()
+            .map(move |(name, constraint)| (name, constraint, origin(set.clone())))
+    })
+}
+
+fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+/// Packages reachable from the direct requirements whose origin matches `roots`.
+fn reachable(state: &State, roots: impl Fn(&Origin) -> bool) -> HashSet<String> {
+    let mut queue: Vec<String> = state
+        .constraints
+        .iter()
+        .filter(|(_, constraints)| constraints.iter().any(|c| roots(&c.origin)))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let mut seen = HashSet::new();
+    while let Some(key) = queue.pop() {
+        if let Some(decision) = state.decided.get(&key) {
+            queue.extend(decision.dependencies.iter().map(|dep| dep.key.to_string()));
+        }
+        seen.insert(key);
     }
+    seen
+}
 
-    Ok(DependencyGraph { packages: resolved })
+/// Combines the same release picked by two forks.
+fn merge_node(
+    existing: &mut ResolutionNode,
+    existing_marker: &mut MarkerTree,
+    node: ResolutionNode,
+    marker: MarkerTree,
+) {
+    existing_marker.or(marker);
+    existing.optional &= node.optional;
+    for group in node.groups {
+        if !existing.groups.contains(&group) {
+            existing.groups.push(group);
+        }
+    }
+    existing.groups.sort();
+    for extra in node.project_extras {
+        if !existing.project_extras.contains(&extra) {
+            existing.project_extras.push(extra);
+        }
+    }
+    existing.project_extras.sort();
+    for dep in node.dependencies {
+        if !existing.dependencies.contains(&dep) {
+            existing.dependencies.push(dep);
+        }
+    }
+    for extra in node.extras {
+        if !existing.extras.contains(&extra) {
+            existing.extras.push(extra);
+        }
+    }
+    existing.extras.sort();
 }
@@ -0,0 +1,44 @@
+//! User-wide settings shared by every project, read from
+//! `~/.wovensnake/config.json`.
+//!
+//! ```json
+//! { "indexUrl": "https://artifactory.example.com/api/pypi/pypi/simple" }
+//! ```
+//!
+//! Anything set here is a machine default: `wovenpkg.json` and command-line
+//! flags take precedence.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::error::WovenError;
+
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Package index used when neither the project nor the environment names one.
+    #[serde(rename = "indexUrl", default)]
+    pub index_url: Option<String>,
+}
+
+impl Settings {
+    /// `~/.wovensnake/config.json`, or `None` without a home directory.
+    pub fn path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".wovensnake").join("config.json"))
+    }
+
+    /// The user's settings; a missing file means the defaults.
+    pub fn load() -> Result<Self, WovenError> {
+        Self::path().map_or_else(|| Ok(Self::default()), |path| Self::read(&path))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, WovenError> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                serde_json::from_str(&content).map_err(|e| WovenError::Config(format!("{}: {e}", path.display())))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(WovenError::Config(format!("cannot read {}: {e}", path.display()))),
+        }
+    }
+}
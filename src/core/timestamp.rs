@@ -0,0 +1,196 @@
+//! Minimal RFC 3339 timestamps, enough to compare `PyPI` upload times with an
+//! `--exclude-newer` cutoff without pulling in a date library.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A point in time parsed from `2026-01-01T00:00:00Z`, an offset form such as
+/// `2026-01-01T09:30:00+02:00`, or a bare date (midnight UTC).
+///
+/// The original text is kept so it round-trips through `wovenpkg.json`.
+#[derive(Debug, Clone)]
+pub struct Timestamp {
+    seconds: i64,
+    nanos: u32,
+    text: String,
+}
+
+impl Timestamp {
+    /// Seconds since the Unix epoch, UTC.
+    pub const fn unix_seconds(&self) -> i64 {
+        self.seconds
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        (self.seconds, self.nanos) == (other.seconds, other.nanos)
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.seconds, self.nanos).cmp(&(other.seconds, other.nanos))
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+const fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn number(text: &str, range: std::ops::RangeInclusive<i64>) -> Option<i64> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok().filter(|n| range.contains(n))
+}
+
+fn parse(text: &str) -> Option<(i64, u32)> {
+    let (date, time) = match text.get(10..11) {
+        None => (text, None),
+        Some("T" | "t" | " ") => (&text[..10], Some(&text[11..])),
+        Some(_) => return None,
+    };
+
+    let mut parts = date.split('-');
+    let year = number(parts.next()?, 0..=9999)?;
+    let month = number(parts.next()?, 1..=12)?;
+    let day = number(parts.next()?, 1..=31)?;
+    if parts.next().is_some() || date.len() != 10 || day > days_in_month(year, month) {
+        return None;
+    }
+    let midnight = days_from_civil(year, month, day) * 86_400;
+
+    let Some(time) = time else {
+        return Some((midnight, 0));
+    };
+
+    let hour = number(time.get(0..2)?, 0..=23)?;
+    let minute = number(time.get(3..5)?, 0..=59)?;
+    let second = number(time.get(6..8)?, 0..=60)?;
+    if time.get(2..3)? != ":" || time.get(5..6)? != ":" {
+        return None;
+    }
+
+    let mut rest = &time[8..];
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        let padded = format!("{:0<9}", &fraction[..digits.min(9)]);
+        nanos = padded.parse().ok()?;
+        rest = &fraction[digits..];
+    }
+
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.get(0..1)? {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            if rest.len() != 6 || rest.get(3..4)? != ":" {
+                return None;
+            }
+            sign * (number(&rest[1..3], 0..=23)? * 3600 + number(&rest[4..6], 0..=59)? * 60)
+        }
+    };
+
+    Some((midnight + hour * 3600 + minute * 60 + second - offset, nanos))
+}
+
+impl FromStr for Timestamp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        parse(text)
+            .map(|(seconds, nanos)| Self {
+                seconds,
+                nanos,
+                text: text.to_string(),
+            })
+            .ok_or_else(|| format!("invalid timestamp '{s}', expected RFC 3339 such as 2026-01-01T00:00:00Z"))
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(text: &str) -> Timestamp {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parses_utc_offset_and_date_forms() {
+        assert_eq!(ts("1970-01-01T00:00:00Z").unix_seconds(), 0);
+        assert_eq!(ts("2026-01-01T00:00:00Z").unix_seconds(), 1_767_225_600);
+        assert_eq!(ts("2026-01-01"), ts("2026-01-01T00:00:00Z"));
+        assert_eq!(ts("2026-01-01T02:00:00+02:00"), ts("2026-01-01T00:00:00Z"));
+        assert!(ts("2023-05-22T15:12:44.175541Z") > ts("2023-05-22T15:12:44Z"));
+        assert_eq!(ts("2024-02-29T12:00:00Z").to_string(), "2024-02-29T12:00:00Z");
+    }
+
+    #[test]
+    fn test_rejects_malformed_timestamps() {
+        for bad in [
+            "",
+            "2026",
+            "2026-13-01",
+            "2023-02-29",
+            "2026-01-01T00:00",
+            "2026-01-01T00:00:00",
+            "yesterday",
+        ] {
+            assert!(bad.parse::<Timestamp>().is_err(), "{bad} should not parse");
+        }
+    }
+}
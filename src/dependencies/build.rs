@@ -0,0 +1,48 @@
+//! Wheels built from source trees: git checkouts and local project directories.
+//!
+//! Building is delegated to `pip wheel`, which runs whatever build backend
+//! the project's `pyproject.toml` or `setup.py` declares.
+
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::core::error::WovenError;
+
+/// A freshly built wheel.
+#[derive(Debug)]
+pub struct Wheel {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+/// Builds `project` into a wheel with the Python matching `python_version`.
+pub async fn build_wheel(python_version: &str, project: &Path) -> Result<Wheel, WovenError> {
+    let python = crate::core::python::find_python_executable(python_version).await?;
+    let out_dir = tempfile::tempdir()?;
+    let output = Command::new(&python)
+        .args(["-m", "pip", "wheel", "--no-deps", "--quiet", "--wheel-dir"])
+        .arg(out_dir.path())
+        .arg(project)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(WovenError::PythonExecution(format!(
+            "could not build {}: {}",
+            project.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let wheel = std::fs::read_dir(out_dir.path())?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("whl")))
+        .ok_or_else(|| WovenError::PythonExecution(format!("building {} produced no wheel", project.display())))?;
+    Ok(Wheel {
+        filename: wheel
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or("built wheel has no file name")?,
+        data: tokio::fs::read(&wheel).await?,
+    })
+}
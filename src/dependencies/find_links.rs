@@ -0,0 +1,95 @@
+//! `findLinks`: prebuilt distributions outside any index.
+//!
+//! Each location is a local directory of wheels and sdists (a wheelhouse),
+//! a local HTML file, or a URL of a flat HTML page linking to distributions
+//! of any project. Local files are recorded as `file://` URLs with the
+//! sha256 of their contents, so a lockfile resolved from a wheelhouse
+//! installs from it without network access.
+
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::core::error::WovenError;
+use crate::dependencies::index::{self, package_type, parse_project_page, release_version};
+use crate::dependencies::metadata;
+use crate::dependencies::package::{Digests, PackageUrl};
+
+/// Every distribution of project `name` across `locations`, or `None` if
+/// no location has one.
+pub async fn project_files(locations: &[String], name: &str) -> Result<Option<Vec<PackageUrl>>, WovenError> {
+    let mut files = Vec::new();
+    for location in locations {
+        let listed = if location.starts_with("http://") || location.starts_with("https://") {
+            let page = crate::core::auth::register(location);
+            let html = crate::core::http_cache::METADATA
+                .fetch(&page)
+                .await?
+                .ok_or_else(|| format!("Could not read find-links page {page}"))?;
+            parse_project_page(&page, &html)?
+        } else {
+            let path = index::local_file(location).unwrap_or_else(|| location.into());
+            local_files(&path)?
+        };
+        for mut file in listed {
+            if release_version(&file.filename, name).is_none() {
+                continue;
+            }
+            if let Some(path) = index::local_file(&file.url) {
+                describe_local(&path, &mut file)?;
+            }
+            files.push(file);
+        }
+    }
+    Ok((!files.is_empty()).then_some(files))
+}
+
+/// The distributions in the directory `path`, or linked from the HTML file `path`.
+fn local_files(path: &Path) -> Result<Vec<PackageUrl>, WovenError> {
+    let absolute = std::path::absolute(path)?;
+    if absolute.is_file() {
+        let base =
+            Url::from_file_path(&absolute).map_err(|()| format!("invalid find-links path {}", absolute.display()))?;
+        return parse_project_page(base.as_str(), &std::fs::read_to_string(&absolute)?);
+    }
+    let entries = std::fs::read_dir(&absolute)
+        .map_err(|e| WovenError::Config(format!("cannot read find-links directory {}: {e}", path.display())))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(filename) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let (Some(packagetype), Ok(url)) = (package_type(&filename), Url::from_file_path(&path)) else {
+            continue;
+        };
+        files.push(PackageUrl {
+            url: url.to_string(),
+            filename,
+            packagetype: packagetype.to_string(),
+            ..PackageUrl::default()
+        });
+    }
+    files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    Ok(files)
+}
+
+/// Fills in the hash of a local distribution, and its `Requires-Python` so
+/// incompatible releases are skipped before their metadata is needed.
+fn describe_local(path: &Path, file: &mut PackageUrl) -> Result<(), WovenError> {
+    let data = std::fs::read(path).map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    let sha256 = format!("{:x}", Sha256::digest(&data));
+    if !file.digests.sha256.is_empty() && file.digests.sha256 != sha256 {
+        return Err(WovenError::CorruptPackage(format!(
+            "{}: sha256 {sha256} does not match the link's {}",
+            file.filename, file.digests.sha256
+        )));
+    }
+    file.digests = Digests { sha256 };
+    if file.requires_python.is_none() {
+        file.requires_python = metadata::metadata_from_archive(&file.filename, &data)
+            .ok()
+            .and_then(|text| metadata::field(&text, "requires-python"));
+    }
+    Ok(())
+}
@@ -0,0 +1,583 @@
+//! Package indexes.
+//!
+//! Every index, `PyPI` included, is read through the Simple Repository API:
+//! one page per project listing each file with its hash, `Requires-Python`,
+//! yank status and PEP 658 metadata availability. Pages are requested in the
+//! PEP 691 JSON form, which also carries PEP 700 upload times, and read as
+//! PEP 503 HTML (links with `data-*` attributes) from indexes that only
+//! serve that. Core metadata then comes from the wheels themselves, so
+//! Artifactory, devpi or a plain directory served over HTTP all work.
+
+use reqwest::Url;
+use std::collections::HashMap;
+
+use crate::core::auth;
+use crate::core::cache::Cache;
+use crate::core::config::Config;
+use crate::core::error::WovenError;
+use crate::core::name::PackageName;
+use crate::core::offline;
+use crate::core::settings::Settings;
+use crate::dependencies::find_links;
+use crate::dependencies::metadata;
+use crate::dependencies::package::{self, CoreMetadata, Digests, Info, PackageUrl};
+
+/// The public `PyPI` simple index, used unless another index is configured.
+pub const DEFAULT_INDEX_URL: &str = "https://pypi.org/simple";
+
+/// Environment variable naming the index when `wovenpkg.json` does not.
+pub const INDEX_URL_ENV: &str = "WOVEN_INDEX_URL";
+
+/// The index URL for a project whose `wovenpkg.json` sets `project`.
+///
+/// `wovenpkg.json` (or `--index-url`, which overrides it) comes first, then
+/// `WOVEN_INDEX_URL`, then `indexUrl` in `~/.wovensnake/config.json`, then `PyPI`.
+pub fn configured_url(project: Option<&str>) -> Result<String, WovenError> {
+    let nonempty = |url: &str| Some(url.trim().to_string()).filter(|url| !url.is_empty());
+    if let Some(url) = project.and_then(nonempty) {
+        return Ok(url);
+    }
+    if let Some(url) = std::env::var(INDEX_URL_ENV).ok().as_deref().and_then(nonempty) {
+        return Ok(url);
+    }
+    Ok(Settings::load()?
+        .index_url
+        .as_deref()
+        .and_then(nonempty)
+        .unwrap_or_else(|| DEFAULT_INDEX_URL.to_string()))
+}
+
+/// A package index, addressed by its simple API root, e.g. `https://pypi.org/simple`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Index {
+    url: String,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self::new(DEFAULT_INDEX_URL)
+    }
+}
+
+impl Index {
+    /// An index at `url`. Credentials in the URL are registered for its
+    /// host and left out of [`Index::url`].
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: auth::register(url.trim()).trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn is_pypi(&self) -> bool {
+        matches!(
+            self.url.as_str(),
+            "https://pypi.org/simple" | "https://pypi.python.org/simple"
+        )
+    }
+
+    /// The project page of `name`; PEP 503 asks for the normalized name and a trailing slash.
+    fn project_url(&self, name: &str) -> String {
+        format!("{}/{}/", self.url, PackageName::new(name))
+    }
+
+    fn not_found(&self, name: &str) -> WovenError {
+        if self.is_pypi() {
+            format!("Could not find package {name} on PyPI").into()
+        } else {
+            format!("Could not find package {name} on {}", self.url).into()
+        }
+    }
+
+    /// Every release of `name`, keyed by version, with its files.
+    pub async fn releases(&self, name: &str) -> Result<HashMap<String, Vec<PackageUrl>>, WovenError> {
+        self.find_releases(name).await?.ok_or_else(|| self.not_found(name))
+    }
+
+    /// Like [`Index::releases`], but `None` when the index does not have the project.
+    pub async fn find_releases(&self, name: &str) -> Result<Option<HashMap<String, Vec<PackageUrl>>>, WovenError> {
+        let files = package::fetch_project_files(&self.project_url(name)).await?;
+        Ok(files.map(|files| group_releases(name, files)))
+    }
+
+    /// The files of one release of `name`.
+    pub async fn release_files(&self, name: &str, version: &str) -> Result<Vec<PackageUrl>, WovenError> {
+        self.releases(name)
+            .await?
+            .remove(version)
+            .ok_or_else(|| format!("{name} {version} is not on {}", self.url).into())
+    }
+
+    /// Core metadata of one release of `name`; see [`release_metadata`].
+    pub async fn release_metadata(&self, name: &str, version: &str) -> Result<Info, WovenError> {
+        release_metadata(name, version, &self.release_files(name, version).await?).await
+    }
+}
+
+/// Core metadata of one release of `name` with the given `files`, read from
+/// one of its wheels, or from the sdist's `PKG-INFO` when it has no wheel.
+///
+/// Files already on disk, as `file://` URLs or in the artifact cache, are
+/// read there.
+pub async fn release_metadata(name: &str, version: &str, files: &[PackageUrl]) -> Result<Info, WovenError> {
+    let text = if let Some(wheel) = metadata::metadata_wheel(files) {
+        match local_copy(wheel) {
+            Some(path) => metadata::metadata_from_archive(&wheel.filename, &tokio::fs::read(path).await?)?,
+            None => metadata::fetch_wheel_metadata(wheel).await?,
+        }
+    } else {
+        let sdist = files
+            .iter()
+            .find(|f| f.packagetype == "sdist")
+            .ok_or_else(|| format!("{name} {version} has no wheel or sdist"))?;
+        let data = if let Some(path) = local_copy(sdist) {
+            tokio::fs::read(path).await?
+        } else {
+            offline::check(|| format!("{} (to read the metadata of {name} {version})", sdist.url))?;
+            let response = crate::core::http::get(&sdist.url).send().await?;
+            if !response.status().is_success() {
+                return Err(format!("Could not download {} ({})", sdist.url, response.status()).into());
+            }
+            response.bytes().await?.to_vec()
+        };
+        metadata::metadata_from_archive(&sdist.filename, &data)?
+    };
+    Ok(Info {
+        name: metadata::field(&text, "name").unwrap_or_else(|| name.to_string()),
+        version: metadata::field(&text, "version").unwrap_or_else(|| version.to_string()),
+        summary: metadata::field(&text, "summary"),
+        requires_dist: Some(metadata::requires_dist(&text)),
+    })
+}
+
+/// Where `file` already is on this machine: behind its `file://` URL, or
+/// in the artifact cache after an earlier install.
+fn local_copy(file: &PackageUrl) -> Option<std::path::PathBuf> {
+    local_file(&file.url).or_else(|| {
+        let cached = Cache::init().ok()?.get_pkg_path(&file.filename, &file.digests.sha256);
+        (!file.digests.sha256.is_empty() && cached.exists()).then_some(cached)
+    })
+}
+
+/// The path behind a `file://` URL.
+pub fn local_file(url: &str) -> Option<std::path::PathBuf> {
+    Url::parse(url)
+        .ok()
+        .filter(|url| url.scheme() == "file")?
+        .to_file_path()
+        .ok()
+}
+
+/// Groups `files` of project `name` by the version in their file names.
+fn group_releases(name: &str, files: Vec<PackageUrl>) -> HashMap<String, Vec<PackageUrl>> {
+    let mut releases: HashMap<String, Vec<PackageUrl>> = HashMap::new();
+    for file in files {
+        if let Some(version) = release_version(&file.filename, name) {
+            releases.entry(version).or_default().push(file);
+        }
+    }
+    releases
+}
+
+/// Every index a project resolves against.
+///
+/// Named indexes from `"indexes"` are searched in order, then the default
+/// one (`indexUrl` and its fallbacks). The first index that has a project
+/// supplies all of its releases: versions are never merged across indexes,
+/// so a look-alike upload to a later index cannot outbid the real package.
+/// An index that fails to answer is an error rather than a miss, for the
+/// same reason. A package pinned in `"sources"` is only looked up on its
+/// index, and explicit indexes serve pinned packages only. `findLinks`
+/// locations come before every index, except for pinned packages.
+#[derive(Debug, Clone, Default)]
+pub struct Indexes {
+    /// Consulted after every named index.
+    pub default: Index,
+    /// Named indexes in priority order, with whether each is explicit.
+    named: Vec<(String, Index, bool)>,
+    /// Index name each pinned package must come from.
+    pins: HashMap<PackageName, String>,
+    /// `findLinks` directories, HTML files and pages; see [`find_links`](crate::dependencies::find_links).
+    pub find_links: Vec<String>,
+}
+
+impl Indexes {
+    pub fn new(default: Index) -> Self {
+        Self {
+            default,
+            ..Self::default()
+        }
+    }
+
+    /// The indexes `config` declares, with pins checked against them.
+    pub fn from_config(config: &Config) -> Result<Self, WovenError> {
+        let mut indexes = Self::new(Index::new(&configured_url(config.index_url.as_deref())?));
+        for index in &config.indexes {
+            if indexes.named.iter().any(|(name, ..)| *name == index.name) {
+                return Err(WovenError::Config(format!("index {} is defined twice", index.name)));
+            }
+            auth::register_env(&index.name, &index.url);
+            indexes
+                .named
+                .push((index.name.clone(), Index::new(&index.url), index.explicit));
+        }
+        for (package, source) in &config.sources {
+            if !indexes.named.iter().any(|(name, ..)| *name == source.index) {
+                return Err(WovenError::Config(format!(
+                    "{package} is pinned to index {}, which is not defined in \"indexes\"",
+                    source.index
+                )));
+            }
+            indexes.pins.insert(PackageName::new(package), source.index.clone());
+        }
+        indexes.find_links.clone_from(&config.find_links);
+        Ok(indexes)
+    }
+
+    /// The indexes `name` may come from, most preferred first.
+    fn candidates(&self, name: &str) -> Vec<&Index> {
+        if let Some(pinned) = self.pins.get(&PackageName::new(name)) {
+            return self
+                .named
+                .iter()
+                .filter(|(index_name, ..)| index_name == pinned)
+                .map(|(_, index, _)| index)
+                .collect();
+        }
+        self.named
+            .iter()
+            .filter(|(.., explicit)| !explicit)
+            .map(|(_, index, _)| index)
+            .chain(std::iter::once(&self.default))
+            .collect()
+    }
+
+    /// The first index that has `name`, with every release it lists. The
+    /// index is `None` when the releases come from `findLinks`.
+    pub async fn find(&self, name: &str) -> Result<(Option<Index>, HashMap<String, Vec<PackageUrl>>), WovenError> {
+        if !self.find_links.is_empty() && !self.pins.contains_key(&PackageName::new(name)) {
+            if let Some(files) = find_links::project_files(&self.find_links, name).await? {
+                return Ok((None, group_releases(name, files)));
+            }
+        }
+        let candidates = self.candidates(name);
+        for index in &candidates {
+            if let Some(releases) = index.find_releases(name).await? {
+                return Ok((Some((*index).clone()), releases));
+            }
+        }
+        if let [index] = candidates.as_slice() {
+            return Err(index.not_found(name));
+        }
+        let urls: Vec<&str> = candidates.iter().map(|index| index.url()).collect();
+        Err(format!("Could not find package {name} on any index ({})", urls.join(", ")).into())
+    }
+
+    /// Every release of `name` on the first index that has it.
+    pub async fn releases(&self, name: &str) -> Result<HashMap<String, Vec<PackageUrl>>, WovenError> {
+        Ok(self.find(name).await?.1)
+    }
+
+    /// Core metadata of one release of `name`, from the first index that has it.
+    pub async fn release_metadata(&self, name: &str, version: &str) -> Result<Info, WovenError> {
+        release_metadata(name, version, &self.release_files(name, version).await?).await
+    }
+
+    /// The files of one release of `name`, from the first index that has it.
+    pub async fn release_files(&self, name: &str, version: &str) -> Result<Vec<PackageUrl>, WovenError> {
+        let (index, mut releases) = self.find(name).await?;
+        releases.remove(version).ok_or_else(|| {
+            let location = index.map_or_else(|| "findLinks".to_string(), |index| index.url().to_string());
+            format!("{name} {version} is not on {location}").into()
+        })
+    }
+}
+
+/// Reads the files listed on a PEP 503 project page served at `page_url`.
+///
+/// Links are resolved against the page, so both absolute and relative
+/// `href`s work. Files that are not wheels or sdists are skipped.
+pub fn parse_project_page(page_url: &str, html: &str) -> Result<Vec<PackageUrl>, WovenError> {
+    let base = Url::parse(page_url).map_err(|e| WovenError::Config(format!("invalid index URL {page_url}: {e}")))?;
+    let mut files = Vec::new();
+    let mut rest = html;
+    while let Some(start) = find_anchor(rest) {
+        let tag = &rest[start + 2..];
+        let Some(end) = tag.find('>') else {
+            break;
+        };
+        let attributes = parse_attributes(&tag[..end]);
+        rest = &tag[end..];
+
+        let Some(href) = attributes.get("href") else {
+            continue;
+        };
+        let Ok(mut url) = base.join(href) else {
+            continue;
+        };
+        let fragment = url.fragment().unwrap_or_default().to_string();
+        url.set_fragment(None);
+        let Some(filename) = url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|name| !name.is_empty())
+            .map(percent_decode)
+        else {
+            continue;
+        };
+        let Some(packagetype) = package_type(&filename) else {
+            continue;
+        };
+
+        let yanked = attributes.get("data-yanked");
+        files.push(PackageUrl {
+            url: url.to_string(),
+            packagetype: packagetype.to_string(),
+            digests: Digests {
+                sha256: fragment
+                    .strip_prefix("sha256=")
+                    .map(str::to_lowercase)
+                    .unwrap_or_default(),
+            },
+            requires_python: attributes
+                .get("data-requires-python")
+                .filter(|spec| !spec.trim().is_empty())
+                .cloned(),
+            yanked: yanked.is_some(),
+            yanked_reason: yanked.filter(|reason| !reason.is_empty()).cloned(),
+            core_metadata: attributes
+                .get("data-core-metadata")
+                .or_else(|| attributes.get("data-dist-info-metadata"))
+                .and_then(|value| core_metadata(value)),
+            filename,
+            ..Default::default()
+        });
+    }
+    Ok(files)
+}
+
+/// Offset of the next `<a` tag in `html`, skipping tags such as `<abbr>`.
+fn find_anchor(html: &str) -> Option<usize> {
+    let bytes = html.as_bytes();
+    let mut from = 0;
+    while let Some(pos) = html[from..].find('<').map(|p| p + from) {
+        let is_anchor = bytes.get(pos + 1).is_some_and(|b| b.eq_ignore_ascii_case(&b'a'))
+            && bytes.get(pos + 2).is_some_and(u8::is_ascii_whitespace);
+        if is_anchor {
+            return Some(pos);
+        }
+        from = pos + 1;
+    }
+    None
+}
+
+/// The attributes of a start tag, names lowercased and values unescaped.
+/// Attributes without a value, like a bare `data-yanked`, map to `""`.
+fn parse_attributes(tag: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = tag.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+        let value = if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (value, remaining) = if let Some(quote @ ('"' | '\'')) = after.chars().next() {
+                let inner = &after[1..];
+                let close = inner.find(quote).unwrap_or(inner.len());
+                (&inner[..close], inner.get(close + 1..).unwrap_or_default())
+            } else {
+                let close = after.find(char::is_whitespace).unwrap_or(after.len());
+                (&after[..close], &after[close..])
+            };
+            rest = remaining;
+            unescape(value)
+        } else {
+            rest = rest.strip_prefix('/').unwrap_or(rest);
+            String::new()
+        };
+        if !name.is_empty() {
+            attributes.entry(name).or_insert(value);
+        }
+        rest = rest.trim_start();
+    }
+    attributes
+}
+
+/// Decodes the character references indexes use in attribute values, such
+/// as `&gt;=3.8` in `data-requires-python`.
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|semi| {
+            let entity = &rest[1..semi];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            }?;
+            Some((c, semi + 1))
+        });
+        let (c, len) = decoded.unwrap_or(('&', 1));
+        out.push(c);
+        rest = &rest[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decodes `%xx` escapes in a URL path segment.
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = || std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok();
+        match (bytes[i], hex().and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// `data-core-metadata="sha256=…"` or `"true"`; anything else means none.
+fn core_metadata(value: &str) -> Option<CoreMetadata> {
+    match value.split_once('=') {
+        Some((algorithm, digest)) => Some(CoreMetadata::Hashes(HashMap::from([(
+            algorithm.to_lowercase(),
+            digest.to_lowercase(),
+        )]))),
+        None if value.eq_ignore_ascii_case("true") => Some(CoreMetadata::Available(true)),
+        None => None,
+    }
+}
+
+const SDIST_SUFFIXES: [&str; 4] = [".tar.gz", ".zip", ".tgz", ".tar.bz2"];
+
+/// `bdist_wheel` or `sdist`, as the JSON API names them; `None` for other
+/// files such as eggs or Windows installers.
+pub fn package_type(filename: &str) -> Option<&'static str> {
+    if filename.to_lowercase().ends_with(".whl") {
+        Some("bdist_wheel")
+    } else if SDIST_SUFFIXES
+        .iter()
+        .any(|suffix| filename.to_lowercase().ends_with(suffix))
+    {
+        Some("sdist")
+    } else {
+        None
+    }
+}
+
+/// The version of project `name` a wheel or sdist file name carries, e.g.
+/// `2.8.2` for `python-dateutil-2.8.2.tar.gz` or `python_dateutil-2.8.2-py2.py3-none-any.whl`.
+pub fn release_version(filename: &str, name: &str) -> Option<String> {
+    let lower = filename.to_lowercase();
+    let wheel = filename.to_lowercase().ends_with(".whl");
+    let stem = if wheel {
+        &filename[..filename.len() - 4]
+    } else {
+        let suffix = SDIST_SUFFIXES.iter().find(|suffix| lower.ends_with(*suffix))?;
+        &filename[..filename.len() - suffix.len()]
+    };
+    let name = PackageName::new(name);
+    // Sdists may keep dashes in the name, so try each split point in turn.
+    stem.match_indices('-').find_map(|(dash, _)| {
+        if PackageName::new(&stem[..dash]) != name {
+            return None;
+        }
+        let rest = &stem[dash + 1..];
+        let version = if wheel { rest.split('-').next()? } else { rest };
+        (!version.is_empty()).then(|| version.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html><body>
+<h1>Links for demo</h1>
+<a href="../../files/demo-1.0.tar.gz#sha256=AB12">demo-1.0.tar.gz</a><br/>
+<a href="https://cdn.example.com/demo-1.0-py3-none-any.whl#sha256=cd34" data-requires-python="&gt;=3.8" data-core-metadata="sha256=ef56">demo-1.0-py3-none-any.whl</a>
+<A HREF='/files/demo-2.0b1-py3-none-any.whl' data-yanked data-dist-info-metadata=true>demo-2.0b1</A>
+<a href="/files/demo-0.9.win32.exe">installer</a>
+<abbr title="x">not a link</abbr>
+</body></html>"#;
+
+    #[test]
+    fn test_parses_simple_project_page() {
+        let files = parse_project_page("https://index.example.com/simple/demo/", PAGE).unwrap();
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].url, "https://index.example.com/files/demo-1.0.tar.gz");
+        assert_eq!(files[0].packagetype, "sdist");
+        assert_eq!(files[0].digests.sha256, "ab12");
+
+        assert_eq!(files[1].filename, "demo-1.0-py3-none-any.whl");
+        assert_eq!(files[1].requires_python.as_deref(), Some(">=3.8"));
+        assert_eq!(
+            files[1].core_metadata.as_ref().and_then(CoreMetadata::sha256),
+            Some("ef56")
+        );
+        assert!(!files[1].yanked);
+
+        assert!(files[2].yanked);
+        assert_eq!(files[2].yanked_reason, None);
+        assert_eq!(files[2].core_metadata, Some(CoreMetadata::Available(true)));
+    }
+
+    #[test]
+    fn test_reads_versions_from_file_names() {
+        assert_eq!(
+            release_version("python-dateutil-2.8.2.tar.gz", "python_dateutil").as_deref(),
+            Some("2.8.2")
+        );
+        assert_eq!(
+            release_version(
+                "zope.interface-6.0-cp312-cp312-manylinux_2_17_x86_64.whl",
+                "zope-interface"
+            )
+            .as_deref(),
+            Some("6.0")
+        );
+        assert_eq!(release_version("demo-1.0.zip", "other"), None);
+        assert_eq!(release_version("demo-1.0.egg", "demo"), None);
+    }
+
+    #[test]
+    fn test_index_url_normalization() {
+        let index = Index::new("https://index.example.com/simple/ ");
+        assert_eq!(index.url(), "https://index.example.com/simple");
+        assert_eq!(
+            index.project_url("Zope.Interface"),
+            "https://index.example.com/simple/zope-interface/"
+        );
+        assert!(Index::default().is_pypi());
+        assert!(!index.is_pypi());
+    }
+}
@@ -0,0 +1,333 @@
+//! Core metadata of individual wheels.
+//!
+//! The simple API lists files but not their requirements. Wheels carry their
+//! own `METADATA`, which indexes publish next to the wheel as
+//! `<wheel url>.metadata` (PEP 658/714) and advertise on the file's link.
+//! When they do not, `METADATA` is read straight out of the remote wheel
+//! with HTTP range requests, so only the zip directory and one member are
+//! downloaded.
+
+use flate2::read::DeflateDecoder;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+use crate::core::error::WovenError;
+use crate::dependencies::package::{CoreMetadata, PackageUrl};
+
+/// Bytes requested from the end of a wheel; enough for the central
+/// directory of all but the largest wheels.
+const TAIL_BYTES: u64 = 64 * 1024;
+
+/// Extra bytes fetched past a member's data to cover a local header whose
+/// extra field is longer than the central directory says.
+const LOCAL_HEADER_SLACK: u64 = 1024;
+
+/// `Requires-Dist` entries of a `METADATA` document, in order.
+pub fn requires_dist(metadata: &str) -> Vec<String> {
+    header_values(metadata, "requires-dist")
+}
+
+/// First value of a single-use header such as `Name` or `Version`.
+pub fn field(metadata: &str, name: &str) -> Option<String> {
+    header_values(metadata, name).into_iter().next()
+}
+
+/// Every value of header `name` (case-insensitive). Folded lines are joined,
+/// and parsing stops at the first blank line, where the description begins.
+fn header_values(metadata: &str, name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut current: Option<String> = None;
+    for line in metadata.lines() {
+        if line.trim().is_empty() {
+            break;
+        }
+        if line.starts_with([' ', '\t']) {
+            if let Some(value) = current.as_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        values.extend(current.take());
+        if let Some((key, value)) = line.split_once(':') {
+            if key.trim().eq_ignore_ascii_case(name) {
+                current = Some(value.trim().to_string());
+            }
+        }
+    }
+    values.extend(current);
+    values
+}
+
+/// Core metadata stored inside a downloaded distribution: `METADATA` of a
+/// wheel, or `PKG-INFO` at the root of a `.tar.gz` / `.zip` sdist.
+pub fn metadata_from_archive(filename: &str, data: &[u8]) -> Result<String, WovenError> {
+    let is_wheel = filename.to_lowercase().ends_with(".whl");
+    let is_tarball = filename.to_lowercase().ends_with(".tar.gz") || filename.to_lowercase().ends_with(".tgz");
+    let wanted = |path: &str| match path.split_once('/') {
+        Some((dir, "METADATA")) => is_wheel && dir.ends_with(".dist-info"),
+        Some((_, "PKG-INFO")) => !is_wheel,
+        _ => false,
+    };
+
+    let mut text = String::new();
+    if is_tarball {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if wanted(&entry.path()?.to_string_lossy()) {
+                entry.read_to_string(&mut text)?;
+                return Ok(text);
+            }
+        }
+    } else {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data))?;
+        let name = archive.file_names().find(|name| wanted(name)).map(ToString::to_string);
+        if let Some(name) = name {
+            archive.by_name(&name)?.read_to_string(&mut text)?;
+            return Ok(text);
+        }
+    }
+    Err(WovenError::CorruptPackage(format!(
+        "{filename}: no core metadata found"
+    )))
+}
+
+/// The wheel among `files` whose metadata is fetched: a pure-Python one
+/// when there is one, since platform wheels occasionally add requirements.
+pub fn metadata_wheel(files: &[PackageUrl]) -> Option<&PackageUrl> {
+    let wheels = || files.iter().filter(|f| f.packagetype == "bdist_wheel" && !f.yanked);
+    wheels()
+        .find(|f| f.filename.ends_with("-none-any.whl"))
+        .or_else(|| wheels().next())
+}
+
+/// `METADATA` of a remote wheel, from its PEP 658 `.metadata` file when the
+/// index advertises one and from the wheel itself otherwise.
+pub async fn fetch_wheel_metadata(wheel: &PackageUrl) -> Result<String, WovenError> {
+    if wheel.core_metadata.as_ref().is_some_and(CoreMetadata::available) {
+        let url = format!("{}.metadata", wheel.url);
+        if let Some(body) = crate::core::http_cache::METADATA.fetch(&url).await? {
+            let expected = wheel.core_metadata.as_ref().and_then(|m| m.sha256());
+            match expected {
+                Some(sha256) if format!("{:x}", Sha256::digest(body.as_bytes())) != sha256 => {
+                    return Err(WovenError::CorruptPackage(format!(
+                        "{}: metadata does not match its published hash",
+                        wheel.filename
+                    )));
+                }
+                _ => return Ok(body),
+            }
+        }
+    }
+    read_remote_metadata(&wheel.url).await
+}
+
+/// A byte range of a remote file, or the whole file if the server ignored
+/// the `Range` header.
+struct Fetched {
+    offset: u64,
+    total: u64,
+    bytes: Vec<u8>,
+}
+
+async fn fetch_range(url: &str, range: &str) -> Result<Fetched, WovenError> {
+    crate::core::offline::check(|| format!("{url} (to read its metadata)"))?;
+    let response = crate::core::http::get(url).header(RANGE, range).send().await?;
+    let status = response.status();
+    if status == StatusCode::OK {
+        let bytes = response.bytes().await?.to_vec();
+        let total = bytes.len() as u64;
+        return Ok(Fetched {
+            offset: 0,
+            total,
+            bytes,
+        });
+    }
+    if status != StatusCode::PARTIAL_CONTENT {
+        return Err(format!("range request for {url} failed with {status}").into());
+    }
+    // `Content-Range: bytes 1000-1999/5000`
+    let (offset, total) = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("bytes "))
+        .and_then(|v| {
+            let (span, total) = v.split_once('/')?;
+            let (start, _) = span.split_once('-')?;
+            Some((start.parse().ok()?, total.parse().ok()?))
+        })
+        .ok_or_else(|| format!("{url}: unusable Content-Range header"))?;
+    let bytes = response.bytes().await?.to_vec();
+    Ok(Fetched { offset, total, bytes })
+}
+
+impl Fetched {
+    /// `len` bytes at absolute position `start`, if this fetch covers them.
+    fn slice(&self, start: u64, len: u64) -> Option<&[u8]> {
+        let from = usize::try_from(start.checked_sub(self.offset)?).ok()?;
+        let to = from.checked_add(usize::try_from(len).ok()?)?;
+        self.bytes.get(from..to)
+    }
+
+    async fn get(&self, url: &str, start: u64, len: u64) -> Result<Vec<u8>, WovenError> {
+        if let Some(bytes) = self.slice(start, len) {
+            return Ok(bytes.to_vec());
+        }
+        let end = (start + len).min(self.total).saturating_sub(1);
+        let fetched = fetch_range(url, &format!("bytes={start}-{end}")).await?;
+        Ok(fetched.slice(start, end + 1 - start).unwrap_or(&fetched.bytes).to_vec())
+    }
+}
+
+async fn read_remote_metadata(url: &str) -> Result<String, WovenError> {
+    let corrupt = || WovenError::CorruptPackage(format!("{url}: no readable METADATA in wheel"));
+
+    let tail = fetch_range(url, &format!("bytes=-{TAIL_BYTES}")).await?;
+    let (cd_offset, cd_size) = central_directory(&tail.bytes, tail.offset).ok_or_else(corrupt)?;
+    let central = tail.get(url, cd_offset, cd_size).await?;
+    let member = find_metadata(&central).ok_or_else(corrupt)?;
+
+    let span = 30 + member.header_len + member.compressed_size + LOCAL_HEADER_SLACK;
+    let local = tail.get(url, member.local_offset, span).await?;
+    let data = read_member(&local, &member).ok_or_else(corrupt)?;
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
+
+/// A `*.dist-info/METADATA` entry of a zip central directory.
+#[derive(Debug, PartialEq, Eq)]
+struct Member {
+    method: u16,
+    compressed_size: u64,
+    local_offset: u64,
+    /// File name plus extra field length recorded in the central directory.
+    header_len: u64,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Offset and size of the central directory, from the end-of-central-directory
+/// record found in `tail`, which starts at absolute position `tail_offset`.
+fn central_directory(tail: &[u8], tail_offset: u64) -> Option<(u64, u64)> {
+    let eocd = (0..=tail.len().checked_sub(22)?)
+        .rev()
+        .find(|&at| u32_at(tail, at) == Some(0x0605_4b50))?;
+    let size = u64::from(u32_at(tail, eocd + 12)?);
+    let offset = u64::from(u32_at(tail, eocd + 16)?);
+    // Zip64 archives store 0xFFFFFFFF here; those are not worth a range read.
+    (offset != u64::from(u32::MAX) && offset + size <= tail_offset + eocd as u64).then_some((offset, size))
+}
+
+fn find_metadata(central: &[u8]) -> Option<Member> {
+    let mut at = 0;
+    while u32_at(central, at)? == 0x0201_4b50 {
+        let method = u16_at(central, at + 10)?;
+        let compressed_size = u64::from(u32_at(central, at + 20)?);
+        let name_len = usize::from(u16_at(central, at + 28)?);
+        let extra_len = usize::from(u16_at(central, at + 30)?);
+        let comment_len = usize::from(u16_at(central, at + 32)?);
+        let local_offset = u64::from(u32_at(central, at + 42)?);
+        let name = std::str::from_utf8(central.get(at + 46..at + 46 + name_len)?).ok()?;
+        if let Some((dir, "METADATA")) = name.split_once('/') {
+            if dir.ends_with(".dist-info") {
+                return Some(Member {
+                    method,
+                    compressed_size,
+                    local_offset,
+                    header_len: (name_len + extra_len) as u64,
+                });
+            }
+        }
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    None
+}
+
+/// Decompressed contents of `member`, given bytes starting at its local header.
+fn read_member(local: &[u8], member: &Member) -> Option<Vec<u8>> {
+    if u32_at(local, 0)? != 0x0403_4b50 {
+        return None;
+    }
+    let start = 30 + usize::from(u16_at(local, 26)?) + usize::from(u16_at(local, 28)?);
+    let data = local.get(start..start + usize::try_from(member.compressed_size).ok()?)?;
+    match member.method {
+        0 => Some(data.to_vec()),
+        8 => {
+            let mut out = Vec::new();
+            DeflateDecoder::new(data).read_to_end(&mut out).ok()?;
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    const METADATA: &str = "Metadata-Version: 2.1\nName: demo\nVersion: 1.0\n\
+        Requires-Dist: idna>=2.5\nRequires-Dist: PySocks!=1.5.7,>=1.5.6;\n extra == \"socks\"\n\
+        Requires-Python: >=3.8\n\nRequires-Dist: not-a-header\n";
+
+    fn wheel(method: zip::CompressionMethod) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(method);
+        zip.start_file("demo/__init__.py", options).unwrap();
+        zip.write_all(&[b'#'; 4096]).unwrap();
+        zip.start_file("demo-1.0.dist-info/METADATA", options).unwrap();
+        zip.write_all(METADATA.as_bytes()).unwrap();
+        zip.start_file("demo-1.0.dist-info/RECORD", options).unwrap();
+        zip.write_all(b"").unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_requires_dist_parses_headers_only() {
+        assert_eq!(
+            requires_dist(METADATA),
+            vec!["idna>=2.5", "PySocks!=1.5.7,>=1.5.6; extra == \"socks\""]
+        );
+        assert_eq!(field(METADATA, "version").as_deref(), Some("1.0"));
+        assert_eq!(field(METADATA, "summary"), None);
+    }
+
+    #[test]
+    fn test_reads_metadata_from_downloaded_wheel() {
+        let bytes = wheel(zip::CompressionMethod::Deflated);
+        assert_eq!(
+            metadata_from_archive("demo-1.0-py3-none-any.whl", &bytes).unwrap(),
+            METADATA
+        );
+        assert!(metadata_from_archive("demo-1.0.zip", &bytes).is_err());
+    }
+
+    #[test]
+    fn test_reads_metadata_member_from_zip_tail() {
+        for method in [zip::CompressionMethod::Stored, zip::CompressionMethod::Deflated] {
+            let bytes = wheel(method);
+            let whole = Fetched {
+                offset: 0,
+                total: bytes.len() as u64,
+                bytes: bytes.clone(),
+            };
+            // Pretend only the last 200 bytes were fetched.
+            let tail_offset = bytes.len() - 200;
+            let (offset, size) = central_directory(&bytes[tail_offset..], tail_offset as u64).unwrap();
+            let member = find_metadata(whole.slice(offset, size).unwrap()).unwrap();
+            let local = whole.slice(member.local_offset, 30 + member.header_len + member.compressed_size);
+            let data = read_member(local.unwrap(), &member).unwrap();
+            assert_eq!(String::from_utf8(data).unwrap(), METADATA);
+        }
+    }
+}
@@ -1 +1,6 @@
+pub mod build;
+pub mod find_links;
+pub mod git;
+pub mod index;
+pub mod metadata;
 pub mod package;
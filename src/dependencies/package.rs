@@ -1,7 +1,10 @@
 use flate2::read::GzDecoder;
-use pep508_rs::pep440_rs::{Version, VersionSpecifiers};
+use pep508_rs::pep440_rs::{Operator, Version, VersionSpecifiers};
 use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 use std::io;
 use std::path::Path;
@@ -9,13 +12,11 @@ use std::str::FromStr;
 use tar::Archive;
 use zip::ZipArchive;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct PypiPackageInfo {
-    pub info: Info,
-    pub urls: Vec<PackageUrl>,
-}
+use crate::core::config::PrereleaseMode;
+use crate::core::name::PackageName;
+use crate::core::timestamp::Timestamp;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Info {
     pub name: String,
     pub version: String,
@@ -23,56 +24,455 @@ pub struct Info {
     pub requires_dist: Option<Vec<String>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct PackageUrl {
     pub url: String,
     pub filename: String,
     pub packagetype: String,
     pub digests: Digests,
+    /// PEP 440 specifier for the Python versions this file supports, e.g. `>=3.9`.
+    #[serde(default)]
+    pub requires_python: Option<String>,
+    /// Set when the file has been yanked from the index (PEP 592).
+    #[serde(default)]
+    pub yanked: bool,
+    #[serde(default)]
+    pub yanked_reason: Option<String>,
+    /// When the file was uploaded (PEP 700), as RFC 3339.
+    #[serde(default)]
+    pub upload_time_iso_8601: Option<String>,
+    /// Whether the index serves this wheel's `METADATA` as `<url>.metadata`
+    /// (PEP 658), under its PEP 714 name or the older one.
+    #[serde(
+        rename = "core-metadata",
+        alias = "data-dist-info-metadata",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub core_metadata: Option<CoreMetadata>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Digests {
     pub sha256: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct PypiFullInfo {
-    pub info: Info,
-    pub releases: std::collections::HashMap<String, Vec<PackageUrl>>,
+/// PEP 658 metadata availability: a plain flag, or the file's hashes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CoreMetadata {
+    Available(bool),
+    Hashes(HashMap<String, String>),
 }
 
-pub async fn fetch_package_info(
-    name: &str,
-    version: Option<&str>,
-) -> Result<PypiPackageInfo, crate::core::error::WovenError> {
-    let url = version.map_or_else(
-        || format!("https://pypi.org/pypi/{name}/json"),
-        |v| format!("https://pypi.org/pypi/{name}/{v}/json"),
-    );
+impl CoreMetadata {
+    pub const fn available(&self) -> bool {
+        match self {
+            Self::Available(available) => *available,
+            Self::Hashes(_) => true,
+        }
+    }
+
+    pub fn sha256(&self) -> Option<&str> {
+        match self {
+            Self::Available(_) => None,
+            Self::Hashes(hashes) => hashes.get("sha256").map(String::as_str),
+        }
+    }
+}
+
+/// `Accept` header for simple API project pages: the PEP 691 JSON form where
+/// the index offers it, the PEP 503 HTML form otherwise.
+pub const SIMPLE_API_ACCEPT: &str =
+    "application/vnd.pypi.simple.v1+json, application/vnd.pypi.simple.v1+html;q=0.2, text/html;q=0.01";
+
+/// A project page in the PEP 691 JSON form.
+#[derive(Deserialize, Debug)]
+struct SimpleProject {
+    meta: SimpleMeta,
+    files: Vec<SimpleFile>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SimpleMeta {
+    #[serde(rename = "api-version")]
+    api_version: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SimpleFile {
+    filename: String,
+    url: String,
+    #[serde(default)]
+    hashes: HashMap<String, String>,
+    #[serde(rename = "requires-python", default)]
+    requires_python: Option<String>,
+    /// `true`, or the reason as a string.
+    #[serde(default)]
+    yanked: serde_json::Value,
+    #[serde(rename = "core-metadata", default)]
+    core_metadata: Option<CoreMetadata>,
+    /// The pre-PEP 714 name, which `PyPI` still sends alongside `core-metadata`.
+    #[serde(rename = "data-dist-info-metadata", alias = "dist-info-metadata", default)]
+    dist_info_metadata: Option<CoreMetadata>,
+    #[serde(rename = "upload-time", default)]
+    upload_time: Option<String>,
+}
 
-    let response = crate::core::http::CLIENT.get(url).send().await?;
+/// Reads the files listed on a PEP 691 JSON project page served at `page_url`.
+///
+/// Relative file URLs are resolved against the page. Files that are not
+/// wheels or sdists are skipped.
+pub fn parse_simple_json(page_url: &str, body: &str) -> Result<Vec<PackageUrl>, crate::core::error::WovenError> {
+    let project: SimpleProject = serde_json::from_str(body).map_err(|e| format!("{page_url}: {e}"))?;
+    if project.meta.api_version.split('.').next() != Some("1") {
+        return Err(format!(
+            "{page_url}: unsupported simple API version {}",
+            project.meta.api_version
+        )
+        .into());
+    }
+    let base = reqwest::Url::parse(page_url).map_err(|e| format!("invalid index URL {page_url}: {e}"))?;
+    Ok(project
+        .files
+        .into_iter()
+        .filter_map(|file| {
+            let packagetype = super::index::package_type(&file.filename)?;
+            let url = base.join(&file.url).ok()?;
+            let (yanked, yanked_reason) = match file.yanked {
+                serde_json::Value::Bool(yanked) => (yanked, None),
+                serde_json::Value::String(reason) => (true, Some(reason).filter(|r| !r.is_empty())),
+                _ => (false, None),
+            };
+            Some(PackageUrl {
+                url: url.to_string(),
+                filename: file.filename,
+                packagetype: packagetype.to_string(),
+                digests: Digests {
+                    sha256: file.hashes.get("sha256").map(|h| h.to_lowercase()).unwrap_or_default(),
+                },
+                requires_python: file.requires_python.filter(|spec| !spec.trim().is_empty()),
+                yanked,
+                yanked_reason,
+                upload_time_iso_8601: file.upload_time,
+                core_metadata: file.core_metadata.or(file.dist_info_metadata),
+            })
+        })
+        .collect())
+}
 
-    if response.status().is_success() {
-        let info: PypiPackageInfo = response.json().await?;
-        Ok(info)
+/// Every file on the simple API project page at `page_url`, or `None` if the
+/// index does not have the project.
+///
+/// The JSON form is requested first; indexes that only speak HTML answer
+/// with that instead, and responses cached without a `Content-Type` are
+/// recognised by their first character.
+pub async fn fetch_project_files(page_url: &str) -> Result<Option<Vec<PackageUrl>>, crate::core::error::WovenError> {
+    let Some(page) = crate::core::http_cache::METADATA
+        .fetch_document(page_url, Some(SIMPLE_API_ACCEPT))
+        .await?
+    else {
+        return Ok(None);
+    };
+    let json = page.content_type.as_deref().map_or_else(
+        || page.body.trim_start().starts_with('{'),
+        |content_type| content_type.contains("json"),
+    );
+    if json {
+        parse_simple_json(page_url, &page.body).map(Some)
     } else {
-        Err(format!("Could not find package {name} on PyPI").into())
+        super::index::parse_project_page(page_url, &page.body).map(Some)
     }
 }
 
-pub async fn fetch_full_package_info(name: &str) -> Result<PypiFullInfo, crate::core::error::WovenError> {
-    let url = format!("https://pypi.org/pypi/{name}/json");
-    let response = crate::core::http::CLIENT.get(url).send().await?;
-    if response.status().is_success() {
-        let info: PypiFullInfo = response.json().await?;
-        Ok(info)
+/// A distribution referenced directly by URL, e.g. `pkg @ https://host/pkg-1.0-py3-none-any.whl`.
+#[derive(Debug)]
+pub struct DirectArtifact {
+    pub info: Info,
+    pub file: PackageUrl,
+    /// Full commit SHA a `git+` requirement was resolved to.
+    pub commit: Option<String>,
+}
+
+/// Downloads the distribution behind a direct URL requirement, hashes it and
+/// reads its core metadata. A `#sha256=` fragment on the URL is verified.
+///
+/// The file is stored in the global cache so installing it later does not
+/// download it again. `file://` URLs are read from disk, `git+` URLs are
+/// checked out, and local project directories such as `../libs/foo` are
+/// built into a wheel with the Python of `python_version`.
+pub async fn fetch_direct_artifact(
+    url: &str,
+    python_version: &str,
+) -> Result<DirectArtifact, crate::core::error::WovenError> {
+    let (location, fragment) = url.split_once('#').unwrap_or((url, ""));
+    let (location, filename, data, commit) = if super::git::is_git_url(url) {
+        let built = super::git::build(url, python_version).await?;
+        (built.url, built.wheel.filename, built.wheel.data, Some(built.commit))
+    } else if let Some(local) = crate::core::config::local_path(url) {
+        let path = Path::new(local.path);
+        let (filename, data) = if path.is_dir() {
+            let wheel = super::build::build_wheel(python_version, path).await?;
+            (wheel.filename, wheel.data)
+        } else {
+            let filename = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .ok_or_else(|| format!("{url}: path does not name a project directory or distribution file"))?;
+            (filename, tokio::fs::read(path).await?)
+        };
+        (local.path.to_string(), filename, data, None)
     } else {
-        Err(format!("Could not find package {name} on PyPI").into())
+        // Credentials in the URL go to the request, never into the lock.
+        let location = crate::core::auth::register(location);
+        let filename = location
+            .split('?')
+            .next()
+            .and_then(|path| path.rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| format!("{location}: URL does not name a distribution file"))?
+            .to_string();
+        let data = if let Some(path) = location.strip_prefix("file://") {
+            tokio::fs::read(path).await?
+        } else {
+            crate::core::offline::check(|| location.clone())?;
+            let response = crate::core::http::get(&location).send().await?;
+            if !response.status().is_success() {
+                return Err(format!("Could not download {location} ({})", response.status()).into());
+            }
+            response.bytes().await?.to_vec()
+        };
+        (location, filename, data, None)
+    };
+
+    let sha256 = format!("{:x}", sha2::Sha256::digest(&data));
+    if let Some(expected) = fragment.strip_prefix("sha256=") {
+        if !expected.eq_ignore_ascii_case(&sha256) {
+            return Err(crate::core::error::WovenError::CorruptPackage(format!(
+                "{filename}: sha256 {sha256} does not match the URL's {expected}"
+            )));
+        }
+    }
+
+    let metadata = super::metadata::metadata_from_archive(&filename, &data)?;
+    let info = Info {
+        name: super::metadata::field(&metadata, "name").ok_or_else(|| format!("{filename}: metadata has no Name"))?,
+        version: super::metadata::field(&metadata, "version")
+            .ok_or_else(|| format!("{filename}: metadata has no Version"))?,
+        summary: super::metadata::field(&metadata, "summary"),
+        requires_dist: Some(super::metadata::requires_dist(&metadata)),
+    };
+
+    if let Ok(cache) = crate::core::cache::Cache::init() {
+        if let Err(e) = cache.save(&filename, &sha256, &data) {
+            log::debug!("could not cache {filename}: {e}");
+        }
+    }
+
+    Ok(DirectArtifact {
+        file: PackageUrl {
+            url: location,
+            packagetype: if filename.to_lowercase().ends_with(".whl") {
+                "bdist_wheel".to_string()
+            } else {
+                "sdist".to_string()
+            },
+            filename,
+            digests: Digests { sha256 },
+            requires_python: super::metadata::field(&metadata, "requires-python"),
+            ..Default::default()
+        },
+        info,
+        commit,
+    })
+}
+
+/// Release-level rules applied on top of version specifiers when choosing
+/// candidates.
+#[derive(Debug, Clone, Default)]
+pub struct CandidatePolicy {
+    /// Python the project targets; releases whose `Requires-Python` excludes
+    /// it are skipped.
+    pub python_version: Option<Version>,
+    pub prerelease: PrereleaseMode,
+    /// Packages allowed pre-releases under [`PrereleaseMode::AllowListed`].
+    pub prerelease_packages: Vec<String>,
+    /// Files uploaded after this moment are treated as if they did not exist.
+    pub exclude_newer: Option<Timestamp>,
+}
+
+impl CandidatePolicy {
+    /// Whether `file` was published by the `exclude_newer` cutoff. Files
+    /// without an upload time are kept, since the index gave nothing to judge.
+    pub fn admits(&self, file: &PackageUrl) -> bool {
+        let (Some(cutoff), Some(uploaded)) = (&self.exclude_newer, &file.upload_time_iso_8601) else {
+            return true;
+        };
+        Timestamp::from_str(uploaded).map_or(true, |uploaded| &uploaded <= cutoff)
+    }
+
+    /// Whether `name` may resolve to a pre-release given `specifiers`, or
+    /// `None` to allow one only when no stable release qualifies.
+    fn allows_prerelease(&self, name: &str, specifiers: &VersionSpecifiers) -> Option<bool> {
+        match self.prerelease {
+            PrereleaseMode::IfNecessary => None,
+            PrereleaseMode::Disallow => Some(false),
+            PrereleaseMode::AllowIfExplicit => Some(specifiers.iter().any(|s| s.version().any_prerelease())),
+            PrereleaseMode::AllowListed => {
+                let name = PackageName::new(name);
+                Some(self.prerelease_packages.iter().any(|p| PackageName::new(p) == name))
+            }
+            PrereleaseMode::AllowAll => Some(true),
+        }
+    }
+}
+
+/// Why a release that matches the version specifiers was passed over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    RequiresPython(String),
+    Yanked(Option<String>),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RequiresPython(spec) => write!(f, "requires Python {spec}"),
+            Self::Yanked(Some(reason)) => write!(f, "yanked ({reason})"),
+            Self::Yanked(None) => write!(f, "yanked"),
+        }
     }
 }
 
-/// Selects the best (highest) version from a `PyPI` releases map that satisfies
+impl SkipReason {
+    /// The reason as shown to the user, naming the project's Python when that
+    /// is what ruled the release out.
+    pub fn describe(&self, python_version: &str) -> String {
+        match self {
+            Self::RequiresPython(_) => format!("{self} (project uses Python {python_version})"),
+            Self::Yanked(_) => self.to_string(),
+        }
+    }
+}
+
+/// Outcome of filtering a releases map: acceptable versions best first
+/// (with the release key they were listed under) and the ones ruled out.
+#[derive(Debug, Default)]
+pub struct Candidates {
+    pub versions: Vec<(Version, String)>,
+    pub skipped: Vec<(Version, SkipReason)>,
+}
+
+/// The `Requires-Python` specifier excluding `python` for every file of a
+/// release, or `None` if at least one file can be installed. Files without
+/// the field (or with an unparsable one) are assumed compatible.
+fn incompatible_python(files: &[PackageUrl], python: &Version) -> Option<String> {
+    let mut excluded = None;
+    for file in files {
+        match file.requires_python.as_deref().map(str::trim) {
+            Some(spec) if !spec.is_empty() => match VersionSpecifiers::from_str(spec) {
+                Ok(specifiers) if !specifiers.contains(python) => excluded = Some(spec.to_string()),
+                _ => return None,
+            },
+            _ => return None,
+        }
+    }
+    excluded
+}
+
+/// A release counts as yanked once every one of its files is.
+pub fn release_yanked(files: &[PackageUrl]) -> bool {
+    !files.is_empty() && files.iter().all(|f| f.yanked)
+}
+
+/// The first non-empty yank reason given by any file of a release.
+pub fn yanked_reason(files: &[PackageUrl]) -> Option<String> {
+    files
+        .iter()
+        .find_map(|f| f.yanked_reason.clone())
+        .filter(|r| !r.is_empty())
+}
+
+/// Whether `specifiers` pin exactly `version` with `==` or `===`, the only
+/// way a yanked release may still be selected.
+fn pins_exactly(specifiers: &VersionSpecifiers, version: &Version) -> bool {
+    specifiers
+        .iter()
+        .any(|s| matches!(s.operator(), Operator::Equal | Operator::ExactEqual) && s.version() == version)
+}
+
+/// Filters the releases map of `name` down to the versions that
+/// satisfy `specifiers` and `policy`, best (highest) first.
+///
+/// Files uploaded after `policy.exclude_newer` are dropped first, and
+/// releases left without files are ignored. Yanked releases are skipped
+/// unless `specifiers` pin them exactly. Pre-releases follow
+/// `policy.prerelease`; by default they are only kept when no stable
+/// version satisfies the constraints.
+pub fn candidate_versions<S: ::std::hash::BuildHasher>(
+    name: &str,
+    releases: &HashMap<String, Vec<PackageUrl>, S>,
+    specifiers: &VersionSpecifiers,
+    policy: &CandidatePolicy,
+) -> Candidates {
+    let mut result = Candidates::default();
+
+    for (key, files) in releases {
+        let files: Cow<'_, [PackageUrl]> = if policy.exclude_newer.is_some() {
+            Cow::Owned(files.iter().filter(|f| policy.admits(f)).cloned().collect())
+        } else {
+            Cow::Borrowed(files)
+        };
+        if files.is_empty() {
+            continue;
+        }
+        let Ok(version) = Version::from_str(key) else {
+            continue;
+        };
+        if !specifiers.contains(&version) {
+            continue;
+        }
+        if let Some(spec) = policy
+            .python_version
+            .as_ref()
+            .and_then(|python| incompatible_python(&files, python))
+        {
+            result.skipped.push((version, SkipReason::RequiresPython(spec)));
+            continue;
+        }
+        if release_yanked(&files) && !pins_exactly(specifiers, &version) {
+            result
+                .skipped
+                .push((version, SkipReason::Yanked(yanked_reason(&files))));
+            continue;
+        }
+        result.versions.push((version, key.clone()));
+    }
+
+    // Prefer stable over pre-release; fall back to pre-release only if nothing stable.
+    let keep_prereleases = policy
+        .allows_prerelease(name, specifiers)
+        .unwrap_or_else(|| result.versions.iter().all(|(v, _)| v.any_prerelease()));
+    if !keep_prereleases {
+        result.versions.retain(|(v, _)| !v.any_prerelease());
+    }
+    result.versions.sort_by(|a, b| b.0.cmp(&a.0));
+    result.skipped.sort_by(|a, b| b.0.cmp(&a.0));
+    result
+}
+
+/// Fetches every release of `name` from `indexes` and filters it with [`candidate_versions`].
+pub async fn fetch_candidates(
+    indexes: &super::index::Indexes,
+    name: &str,
+    specifiers: &VersionSpecifiers,
+    policy: &CandidatePolicy,
+) -> Result<Candidates, crate::core::error::WovenError> {
+    let releases = indexes.releases(name).await?;
+    Ok(candidate_versions(name, &releases, specifiers, policy))
+}
+
+/// Selects the best (highest) version from a releases map that satisfies
 /// all provided PEP440 `specifiers`. Pre-release versions are skipped unless
 /// no stable version satisfies the constraints.
 ///
@@ -81,28 +481,81 @@ pub fn select_best_candidate<S: ::std::hash::BuildHasher>(
     releases: &HashMap<String, Vec<PackageUrl>, S>,
     specifiers: &VersionSpecifiers,
 ) -> Option<String> {
-    let candidates: Vec<Version> = releases
-        .keys()
-        .filter_map(|k| Version::from_str(k).ok())
-        .filter(|v| specifiers.contains(v))
-        .collect();
-
-    // Prefer stable over pre-release; fall back to pre-release only if nothing stable.
-    let stable: Vec<Version> = candidates.iter().filter(|v| !v.any_prerelease()).cloned().collect();
-
-    let pool = if stable.is_empty() { &candidates } else { &stable };
+    select_best_candidate_with("", releases, specifiers, &CandidatePolicy::default())
+}
 
-    pool.iter().max().map(Version::to_string)
+/// [`select_best_candidate`] for package `name` with release-level rules such
+/// as the project's Python version applied.
+pub fn select_best_candidate_with<S: ::std::hash::BuildHasher>(
+    name: &str,
+    releases: &HashMap<String, Vec<PackageUrl>, S>,
+    specifiers: &VersionSpecifiers,
+    policy: &CandidatePolicy,
+) -> Option<String> {
+    candidate_versions(name, releases, specifiers, policy)
+        .versions
+        .into_iter()
+        .next()
+        .map(|(_, key)| key)
 }
 
 pub async fn download_package(url: &str, dest_path: &Path) -> Result<(), crate::core::error::WovenError> {
-    let response = crate::core::http::CLIENT.get(url).send().await?;
+    crate::core::offline::check(|| url.to_string())?;
+    let response = crate::core::http::get(url).send().await?;
     let content = response.bytes().await?;
     tokio::fs::write(dest_path, content).await?;
     Ok(())
 }
 
 pub fn extract_wheel(wheel_path: &Path, dest_path: &Path) -> Result<(), crate::core::error::WovenError> {
+    extract_wheel_members(wheel_path, dest_path, |_| true)
+}
+
+/// Installs a wheel built from `source` in editable mode.
+///
+/// Only the `.dist-info` is unpacked; a `__editable__.<name>.pth` file puts
+/// the project's import root (`source/src` when present) on `sys.path`, so
+/// edits take effect without reinstalling.
+pub fn install_editable(
+    wheel_path: &Path,
+    dest_path: &Path,
+    source: &Path,
+) -> Result<(), crate::core::error::WovenError> {
+    let mut dist_info = None;
+    extract_wheel_members(wheel_path, dest_path, |path| {
+        let top = path
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy().to_string());
+        match top {
+            Some(dir) if dir.ends_with(".dist-info") => {
+                dist_info.get_or_insert(dir);
+                true
+            }
+            _ => false,
+        }
+    })?;
+    let dist_info = dist_info.ok_or_else(|| format!("{}: wheel has no .dist-info", wheel_path.display()))?;
+    let name = dist_info.split('-').next().unwrap_or(&dist_info);
+
+    let source = fs::canonicalize(source)?;
+    let import_root = if source.join("src").is_dir() {
+        source.join("src")
+    } else {
+        source
+    };
+    fs::write(
+        dest_path.join(format!("__editable__.{name}.pth")),
+        format!("{}\n", import_root.display()),
+    )?;
+    Ok(())
+}
+
+fn extract_wheel_members(
+    wheel_path: &Path,
+    dest_path: &Path,
+    mut keep: impl FnMut(&Path) -> bool,
+) -> Result<(), crate::core::error::WovenError> {
     let file = fs::File::open(wheel_path)?;
     let mut archive = ZipArchive::new(file)?;
 
@@ -113,8 +566,8 @@ pub fn extract_wheel(wheel_path: &Path, dest_path: &Path) -> Result<(), crate::c
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
         let outpath = match file.enclosed_name() {
-            Some(path) => dest_path.join(path),
-            None => continue,
+            Some(path) if keep(&path) => dest_path.join(path),
+            _ => continue,
         };
 
         if (*file.name()).ends_with('/') {
@@ -142,96 +595,6 @@ pub fn extract_wheel(wheel_path: &Path, dest_path: &Path) -> Result<(), crate::c
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use flate2::write::GzEncoder;
-    use flate2::Compression;
-    use tar::Builder;
-
-    fn build_sdist_targz(archive_path: &Path) {
-        let file = fs::File::create(archive_path).unwrap();
-        let gz = GzEncoder::new(file, Compression::default());
-        let mut tar = Builder::new(gz);
-
-        // top-level sdist directory entry
-        let mut header = tar::Header::new_gnu();
-        header.set_entry_type(tar::EntryType::Directory);
-        header.set_size(0);
-        header.set_mode(0o755);
-        header.set_cksum();
-        tar.append_data(&mut header, "mypkg-1.0.0/", std::io::empty()).unwrap();
-
-        // sub-package directory
-        let mut header = tar::Header::new_gnu();
-        header.set_entry_type(tar::EntryType::Directory);
-        header.set_size(0);
-        header.set_mode(0o755);
-        header.set_cksum();
-        tar.append_data(&mut header, "mypkg-1.0.0/mypkg/", std::io::empty())
-            .unwrap();
-
-        // __init__.py inside the package
-        let content = b"# mypkg\n";
-        let mut header = tar::Header::new_gnu();
-        header.set_size(content.len() as u64);
-        header.set_mode(0o644);
-        header.set_cksum();
-        tar.append_data(&mut header, "mypkg-1.0.0/mypkg/__init__.py", content.as_ref())
-            .unwrap();
-
-        // setup.py at sdist root
-        let content = b"from setuptools import setup\nsetup()\n";
-        let mut header = tar::Header::new_gnu();
-        header.set_size(content.len() as u64);
-        header.set_mode(0o644);
-        header.set_cksum();
-        tar.append_data(&mut header, "mypkg-1.0.0/setup.py", content.as_ref())
-            .unwrap();
-
-        tar.finish().unwrap();
-    }
-
-    #[test]
-    fn test_extract_targz_strips_toplevel_dir() {
-        let tmp = tempfile::tempdir().unwrap();
-        let archive_path = tmp.path().join("mypkg-1.0.0.tar.gz");
-        let site_packages = tmp.path().join("site-packages");
-        fs::create_dir_all(&site_packages).unwrap();
-
-        build_sdist_targz(&archive_path);
-        extract_targz(&archive_path, &site_packages).unwrap();
-
-        // The package must be importable directly from site-packages
-        assert!(
-            site_packages.join("mypkg/__init__.py").exists(),
-            "mypkg/__init__.py should be at the site-packages root"
-        );
-        // The sdist wrapper directory must NOT exist
-        assert!(
-            !site_packages.join("mypkg-1.0.0").exists(),
-            "sdist root directory should not appear in site-packages"
-        );
-    }
-
-    #[test]
-    fn test_extract_targz_includes_sibling_files() {
-        let tmp = tempfile::tempdir().unwrap();
-        let archive_path = tmp.path().join("mypkg-1.0.0.tar.gz");
-        let site_packages = tmp.path().join("site-packages");
-        fs::create_dir_all(&site_packages).unwrap();
-
-        build_sdist_targz(&archive_path);
-        extract_targz(&archive_path, &site_packages).unwrap();
-
-        // setup.py (a sibling at the sdist root) should also be present after stripping
-        assert!(
-            site_packages.join("setup.py").exists(),
-            "setup.py from sdist root should be extracted"
-        );
-    }
-}
-
 pub fn generate_scripts(
     dist_info_path: &Path,
     scripts_dir: &Path,
@@ -355,3 +718,173 @@ pub fn extract_targz(path: &Path, dest_path: &Path) -> Result<(), crate::core::e
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tar::Builder;
+
+    fn build_sdist_targz(archive_path: &Path) {
+        let file = fs::File::create(archive_path).unwrap();
+        let gz = GzEncoder::new(file, Compression::default());
+        let mut tar = Builder::new(gz);
+
+        // top-level sdist directory entry
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        header.set_mode(0o755);
+        header.set_cksum();
+        tar.append_data(&mut header, "mypkg-1.0.0/", std::io::empty()).unwrap();
+
+        // sub-package directory
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        header.set_mode(0o755);
+        header.set_cksum();
+        tar.append_data(&mut header, "mypkg-1.0.0/mypkg/", std::io::empty())
+            .unwrap();
+
+        // __init__.py inside the package
+        let content = b"# mypkg\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "mypkg-1.0.0/mypkg/__init__.py", content.as_ref())
+            .unwrap();
+
+        // setup.py at sdist root
+        let content = b"from setuptools import setup\nsetup()\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "mypkg-1.0.0/setup.py", content.as_ref())
+            .unwrap();
+
+        tar.finish().unwrap();
+    }
+
+    #[test]
+    fn test_extract_targz_strips_toplevel_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive_path = tmp.path().join("mypkg-1.0.0.tar.gz");
+        let site_packages = tmp.path().join("site-packages");
+        fs::create_dir_all(&site_packages).unwrap();
+
+        build_sdist_targz(&archive_path);
+        extract_targz(&archive_path, &site_packages).unwrap();
+
+        // The package must be importable directly from site-packages
+        assert!(
+            site_packages.join("mypkg/__init__.py").exists(),
+            "mypkg/__init__.py should be at the site-packages root"
+        );
+        // The sdist wrapper directory must NOT exist
+        assert!(
+            !site_packages.join("mypkg-1.0.0").exists(),
+            "sdist root directory should not appear in site-packages"
+        );
+    }
+
+    #[test]
+    fn test_extract_targz_includes_sibling_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive_path = tmp.path().join("mypkg-1.0.0.tar.gz");
+        let site_packages = tmp.path().join("site-packages");
+        fs::create_dir_all(&site_packages).unwrap();
+
+        build_sdist_targz(&archive_path);
+        extract_targz(&archive_path, &site_packages).unwrap();
+
+        // setup.py (a sibling at the sdist root) should also be present after stripping
+        assert!(
+            site_packages.join("setup.py").exists(),
+            "setup.py from sdist root should be extracted"
+        );
+    }
+
+    #[test]
+    fn test_install_editable_links_sources_instead_of_copying() {
+        use std::io::Write;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join("libs").join("foo");
+        fs::create_dir_all(project.join("src").join("foo")).unwrap();
+        let wheel_path = tmp.path().join("foo-0.1.0-py3-none-any.whl");
+        let mut wheel = zip::ZipWriter::new(fs::File::create(&wheel_path).unwrap());
+        for member in ["foo/__init__.py", "foo-0.1.0.dist-info/METADATA"] {
+            wheel
+                .start_file(member, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            wheel.write_all(b"Name: foo\n").unwrap();
+        }
+        wheel.finish().unwrap();
+
+        let site_packages = tmp.path().join("site-packages");
+        install_editable(&wheel_path, &site_packages, &project).unwrap();
+
+        assert!(site_packages.join("foo-0.1.0.dist-info").join("METADATA").exists());
+        assert!(!site_packages.join("foo").exists(), "sources must stay in the project");
+        let pth = fs::read_to_string(site_packages.join("__editable__.foo.pth")).unwrap();
+        assert_eq!(
+            pth.trim(),
+            fs::canonicalize(project.join("src")).unwrap().display().to_string()
+        );
+    }
+
+    #[test]
+    fn test_parses_pep_691_project_page() {
+        let body = r#"{
+            "meta": {"api-version": "1.1"},
+            "name": "demo",
+            "files": [
+                {"filename": "demo-1.0-py3-none-any.whl", "url": "../../files/demo-1.0-py3-none-any.whl",
+                 "hashes": {"sha256": "ABCD"}, "requires-python": ">=3.8",
+                 "core-metadata": {"sha256": "beef"}, "data-dist-info-metadata": {"sha256": "beef"},
+                 "upload-time": "2024-05-01T12:00:00.000000Z", "yanked": false},
+                {"filename": "demo-0.9.tar.gz", "url": "https://files.example.com/demo-0.9.tar.gz",
+                 "hashes": {}, "yanked": "broken build"},
+                {"filename": "demo-0.8.exe", "url": "demo-0.8.exe", "hashes": {}}
+            ],
+            "versions": ["0.8", "0.9", "1.0"]
+        }"#;
+        let files = parse_simple_json("https://index.example.com/simple/demo/", body).unwrap();
+        assert_eq!(files.len(), 2);
+
+        let wheel = &files[0];
+        assert_eq!(wheel.url, "https://index.example.com/files/demo-1.0-py3-none-any.whl");
+        assert_eq!(wheel.packagetype, "bdist_wheel");
+        assert_eq!(wheel.digests.sha256, "abcd");
+        assert_eq!(wheel.requires_python.as_deref(), Some(">=3.8"));
+        assert_eq!(
+            wheel.core_metadata.as_ref().and_then(CoreMetadata::sha256),
+            Some("beef")
+        );
+        assert_eq!(
+            wheel.upload_time_iso_8601.as_deref(),
+            Some("2024-05-01T12:00:00.000000Z")
+        );
+        assert!(!wheel.yanked);
+
+        let sdist = &files[1];
+        assert_eq!(sdist.packagetype, "sdist");
+        assert!(sdist.yanked);
+        assert_eq!(sdist.yanked_reason.as_deref(), Some("broken build"));
+        assert_eq!(sdist.core_metadata, None);
+
+        let legacy = r#"{"meta": {"api-version": "1.0"}, "files": [
+            {"filename": "demo-1.0-py3-none-any.whl", "url": "demo-1.0-py3-none-any.whl",
+             "hashes": {}, "dist-info-metadata": true, "yanked": true}]}"#;
+        let files = parse_simple_json("https://index.example.com/simple/demo/", legacy).unwrap();
+        assert_eq!(files[0].core_metadata, Some(CoreMetadata::Available(true)));
+        assert!(files[0].yanked && files[0].yanked_reason.is_none());
+
+        let future = r#"{"meta": {"api-version": "2.0"}, "files": []}"#;
+        assert!(parse_simple_json("https://index.example.com/simple/demo/", future).is_err());
+    }
+}
@@ -9,7 +9,9 @@
 
 use clap::{Parser, Subcommand};
 use wovensnake::cli;
+use wovensnake::cli::install::{ResolveArgs, SelectionArgs};
 use wovensnake::cli::ux;
+use wovensnake::core::auth::redact_text;
 
 #[derive(Parser)]
 #[command(name = "woven", version)]
@@ -17,6 +19,9 @@ use wovensnake::cli::ux;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Work from the caches and installed Pythons only, without network access
+    #[arg(long, global = true)]
+    offline: bool,
 }
 
 #[derive(Subcommand)]
@@ -34,15 +39,33 @@ enum Commands {
         name: String,
         /// Optional version of the package
         version: Option<String>,
+        #[command(flatten)]
+        resolve: ResolveArgs,
+        #[command(flatten)]
+        selection: SelectionArgs,
     },
     /// Install dependencies, or add and install specific packages
     Install {
         /// Packages to add (e.g. requests, flask==3.0.0). Installs all if omitted.
         #[arg(trailing_var_arg = true)]
         packages: Vec<String>,
+        #[command(flatten)]
+        resolve: ResolveArgs,
+        #[command(flatten)]
+        selection: SelectionArgs,
+    },
+    /// Resolve dependencies and write wovenpkg.lock without installing
+    Lock {
+        #[command(flatten)]
+        resolve: ResolveArgs,
     },
     /// Update dependencies to their latest versions
-    Update,
+    Update {
+        /// Packages to update, from any dependency group or extra. Updates all if omitted.
+        packages: Vec<String>,
+        #[command(flatten)]
+        resolve: ResolveArgs,
+    },
     /// Run a command within the virtual environment
     Run {
         #[arg(trailing_var_arg = true)]
@@ -73,6 +96,9 @@ enum Commands {
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    if cli.offline {
+        wovensnake::core::offline::enable();
+    }
 
     match cli.command {
         Commands::Init { yes } => {
@@ -83,30 +109,44 @@ async fn main() {
                 ux::print_error(format!("Failed to initialize project: {e}"));
             }
         }
-        Commands::Add { name, version } => {
-            if let Err(e) = cli::add::execute(&name, version).await {
-                ux::print_error(format!("Failed to add package '{name}': {e}"));
+        Commands::Add {
+            name,
+            version,
+            resolve,
+            selection,
+        } => {
+            if let Err(e) = cli::add::execute(&name, version, &resolve, &selection).await {
+                ux::print_error(redact_text(&format!("Failed to add package '{name}': {e}")));
             }
         }
-        Commands::Install { packages } => {
+        Commands::Install {
+            packages,
+            resolve,
+            selection,
+        } => {
             if packages.is_empty() {
                 // woven install  →  install all from wovenpkg.json
-                if let Err(e) = cli::install::execute(false).await {
-                    ux::print_error(format!("Installation failed: {e}"));
+                if let Err(e) = cli::install::execute(false, &resolve, &selection).await {
+                    ux::print_error(redact_text(&format!("Installation failed: {e}")));
                 }
             } else {
                 // woven install requests flask==3.0.0  →  add each package then install
                 for pkg in &packages {
-                    if let Err(e) = cli::add::execute(pkg, None).await {
-                        ux::print_error(format!("Failed to add '{pkg}': {e}"));
+                    if let Err(e) = cli::add::execute(pkg, None, &resolve, &selection).await {
+                        ux::print_error(redact_text(&format!("Failed to add '{pkg}': {e}")));
                         break;
                     }
                 }
             }
         }
-        Commands::Update => {
-            if let Err(e) = cli::update::execute().await {
-                ux::print_error(format!("Update failed: {e}"));
+        Commands::Lock { resolve } => {
+            if let Err(e) = cli::lock::execute(&resolve).await {
+                ux::print_error(redact_text(&format!("Locking failed: {e}")));
+            }
+        }
+        Commands::Update { packages, resolve } => {
+            if let Err(e) = cli::update::execute(&packages, &resolve).await {
+                ux::print_error(redact_text(&format!("Update failed: {e}")));
             }
         }
         Commands::Run { args } => {
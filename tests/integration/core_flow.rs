@@ -46,6 +46,7 @@ fn test_lockfile_integrity() {
             version: "2.25.1".to_string(),
             artifacts: vec![],
             dependencies: vec!["urllib3".to_string(), "chardet".to_string()],
+            ..Default::default()
         },
     );
 
@@ -65,6 +66,39 @@ fn test_lockfile_integrity() {
     assert_eq!(pkg.dependencies[0], "urllib3");
 }
 
+#[test]
+fn test_lockfile_records_yanked_status() {
+    let dir = tempdir().unwrap();
+    let lock_path = dir.path().join("wovenpkg.lock");
+
+    let mut lockfile = Lockfile::new("test-project", "0.1.0", "3.10");
+    lockfile.packages.insert(
+        "attrs".to_string(),
+        LockedPackage {
+            version: "23.2.0".to_string(),
+            yanked: true,
+            yanked_reason: Some("broken build".to_string()),
+            ..Default::default()
+        },
+    );
+    lockfile.packages.insert(
+        "idna".to_string(),
+        LockedPackage {
+            version: "3.6".to_string(),
+            ..Default::default()
+        },
+    );
+    lockfile.write(&lock_path).unwrap();
+
+    let raw = fs::read_to_string(&lock_path).unwrap();
+    assert_eq!(raw.matches("\"yanked\"").count(), 1);
+
+    let loaded = Lockfile::read(&lock_path).unwrap();
+    assert!(loaded.packages["attrs"].yanked);
+    assert_eq!(loaded.packages["attrs"].yanked_reason.as_deref(), Some("broken build"));
+    assert!(!loaded.packages["idna"].yanked);
+}
+
 #[test]
 fn test_cache_mechanism() {
     let dir = tempdir().unwrap();
@@ -98,3 +132,160 @@ fn test_cache_mechanism() {
     let linked_content = fs::read(linked_path).unwrap();
     assert_eq!(linked_content, content);
 }
+
+#[test]
+fn test_lockfile_filters_packages_by_marker() {
+    let mut lockfile = Lockfile::new("test-project", "0.1.0", "3.12");
+    let locked = |version: &str, marker: Option<&str>| LockedPackage {
+        version: version.to_string(),
+        marker: marker.map(ToString::to_string),
+        ..Default::default()
+    };
+    lockfile.packages.insert("click".to_string(), locked("8.1.7", None));
+    lockfile
+        .packages
+        .insert("never".to_string(), locked("1.0", Some("python_version < '3'")));
+    lockfile.packages.insert(
+        "numpy==2.1.0".to_string(),
+        locked("2.1.0", Some("python_version >= '3.10'")),
+    );
+
+    let mut names: Vec<_> = lockfile
+        .packages_for_current_platform()
+        .into_iter()
+        .map(|(name, pkg)| format!("{name} {}", pkg.version))
+        .collect();
+    names.sort();
+    assert_eq!(names, ["click 8.1.7", "numpy 2.1.0"]);
+}
+
+#[test]
+fn test_lockfile_read_normalizes_package_names() {
+    let dir = tempdir().unwrap();
+    let lock_path = dir.path().join("wovenpkg.lock");
+    let mut lockfile = Lockfile::new("test-project", "0.1.0", "3.12");
+    for key in ["Zope.Interface", "typing_extensions==4.12.2"] {
+        lockfile.packages.insert(key.to_string(), LockedPackage::default());
+    }
+    lockfile.write(&lock_path).unwrap();
+
+    let loaded = Lockfile::read(&lock_path).unwrap();
+    let mut keys: Vec<_> = loaded.packages.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["typing-extensions==4.12.2", "zope-interface"]);
+}
+
+#[test]
+fn test_lockfile_selects_dependency_groups() {
+    use wovensnake::core::lock::Selection;
+
+    let mut lockfile = Lockfile::new("test-project", "0.1.0", "3.12");
+    let locked = |groups: &[&str], optional: bool| LockedPackage {
+        version: "1.0".to_string(),
+        groups: groups.iter().map(ToString::to_string).collect(),
+        optional,
+        ..Default::default()
+    };
+    lockfile.packages.insert("flask".to_string(), locked(&[], false));
+    lockfile.packages.insert("click".to_string(), locked(&["dev"], false));
+    lockfile.packages.insert("pytest".to_string(), locked(&["dev"], true));
+    lockfile.packages.insert("sphinx".to_string(), locked(&["docs"], true));
+
+    let names = |selection: &Selection| {
+        let mut names: Vec<_> = lockfile
+            .packages_to_install(selection)
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(&Selection::default()), ["click", "flask"]);
+    let dev = Selection {
+        dependencies: true,
+        groups: ["dev".to_string()].into(),
+        ..Selection::default()
+    };
+    assert_eq!(names(&dev), ["click", "flask", "pytest"]);
+    let only_docs = Selection {
+        dependencies: false,
+        groups: ["docs".to_string()].into(),
+        ..Selection::default()
+    };
+    assert_eq!(names(&only_docs), ["sphinx"]);
+}
+
+#[test]
+fn test_lockfile_selects_project_extras() {
+    use wovensnake::core::lock::Selection;
+
+    let mut lockfile = Lockfile::new("test-project", "0.1.0", "3.12");
+    let locked = |extras: &[&str], groups: &[&str], optional: bool| LockedPackage {
+        version: "1.0".to_string(),
+        project_extras: extras.iter().map(ToString::to_string).collect(),
+        groups: groups.iter().map(ToString::to_string).collect(),
+        optional,
+        ..Default::default()
+    };
+    lockfile
+        .packages
+        .insert("sqlalchemy".to_string(), locked(&[], &[], false));
+    lockfile
+        .packages
+        .insert("psycopg".to_string(), locked(&["postgres"], &[], true));
+    lockfile
+        .packages
+        .insert("pymysql".to_string(), locked(&["mysql"], &["dev"], true));
+
+    let names = |selection: &Selection| {
+        let mut names: Vec<_> = lockfile
+            .packages_to_install(selection)
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(&Selection::default()), ["sqlalchemy"]);
+    let postgres = Selection {
+        extras: ["postgres".to_string()].into(),
+        ..Selection::default()
+    };
+    assert_eq!(names(&postgres), ["psycopg", "sqlalchemy"]);
+    let dev = Selection {
+        groups: ["dev".to_string()].into(),
+        ..Selection::default()
+    };
+    assert_eq!(names(&dev), ["pymysql", "sqlalchemy"]);
+    // Extras extend the project's dependencies, so they need them selected.
+    let extras_without_dependencies = Selection {
+        dependencies: false,
+        extras: ["postgres".to_string(), "mysql".to_string()].into(),
+        ..Selection::default()
+    };
+    assert!(names(&extras_without_dependencies).is_empty());
+}
+
+#[test]
+fn test_installed_packages_match_lock_names() {
+    use wovensnake::core::installer;
+    use wovensnake::core::name::PackageName;
+
+    let site_packages = tempdir().unwrap();
+    for dir in [
+        "zope.interface-6.0.dist-info",
+        "foo_bar-1.0.dist-info",
+        "foobar-2.0.dist-info",
+        "zope",
+    ] {
+        fs::create_dir(site_packages.path().join(dir)).unwrap();
+    }
+
+    let installed = installer::installed_packages(site_packages.path());
+    assert_eq!(installed.len(), 3);
+    assert!(installed.contains("zope-interface"));
+    assert!(installed.contains(&PackageName::new("Foo.Bar")));
+
+    let found = installer::find_dist_info(site_packages.path(), &PackageName::new("foo-bar")).unwrap();
+    assert!(found.ends_with("foo_bar-1.0.dist-info"));
+}
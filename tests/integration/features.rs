@@ -20,6 +20,7 @@ async fn test_remove_logic_simulation() {
             ("flask".to_string(), "2.0.0".to_string()),
             ("requests".to_string(), "2.25.0".to_string()),
         ]),
+        ..Default::default()
     };
 
     // emulate saving
@@ -55,6 +56,7 @@ async fn test_add_logic_simulation() {
         python_version: "3.10".into(),
         virtual_environment: "venv".into(),
         dependencies: HashMap::from([("requests".to_string(), "2.25.0".to_string())]),
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&config).unwrap();
@@ -88,6 +90,7 @@ fn test_lockfile_pruning_logic() {
             version: "2.0".into(),
             artifacts: vec![],
             dependencies: vec![],
+            ..Default::default()
         },
     );
     lockfile.packages.insert(
@@ -96,6 +99,7 @@ fn test_lockfile_pruning_logic() {
             version: "2.25".into(),
             artifacts: vec![],
             dependencies: vec![],
+            ..Default::default()
         },
     );
 
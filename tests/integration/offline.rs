@@ -0,0 +1,167 @@
+//! Offline mode is process-wide, so it is exercised in its own test binary.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use wovensnake::core::cache::{Cache, CACHE_DIR_ENV};
+use wovensnake::core::error::WovenError;
+use wovensnake::core::installer::{self, InstallReporter, InstallTaskReporter};
+use wovensnake::core::lock::{Artifact, LockedPackage, Lockfile, Selection};
+use wovensnake::core::offline;
+use wovensnake::core::resolver::{resolve_with, IndexSource, ResolveOptions};
+use wovensnake::dependencies::index::{Index, Indexes};
+
+const METADATA: &str = "Metadata-Version: 2.1\nName: demo\nVersion: 1.0\n";
+
+fn project_page(name: &str) -> String {
+    format!(
+        r#"<a href="/files/{name}-1.0-py3-none-any.whl#sha256=abcd" data-core-metadata="sha256={:x}">{name}-1.0-py3-none-any.whl</a>"#,
+        Sha256::digest(METADATA.as_bytes())
+    )
+}
+
+/// Serves `routes` (path → body) on a random local port, counting requests,
+/// and returns the simple API root.
+async fn serve(routes: HashMap<String, String>, hits: Arc<AtomicUsize>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let routes = Arc::new(routes);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            hits.fetch_add(1, Ordering::SeqCst);
+            let routes = routes.clone();
+            tokio::spawn(async move {
+                let mut buf = vec![0; 8192];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let response = routes.get(path).map_or_else(
+                    || "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_string(),
+                    |body| {
+                        format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                            body.len()
+                        )
+                    },
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    format!("http://{addr}/simple/")
+}
+
+struct Quiet;
+
+impl InstallReporter for Quiet {
+    fn create_task(&self, _name: &str) -> Box<dyn InstallTaskReporter> {
+        Box::new(Quiet)
+    }
+    fn create_spinner(&self, _msg: &str) -> Box<dyn InstallTaskReporter> {
+        Box::new(Quiet)
+    }
+}
+
+impl InstallTaskReporter for Quiet {
+    fn set_message(&self, _msg: String) {}
+    fn finish_success(&self, _msg: String) {}
+    fn finish_error(&self, _msg: String) {}
+    fn warning(&self, _msg: String) {}
+    fn print_line(&self, _msg: String) {}
+    fn finish_and_clear(&self) {}
+}
+
+#[tokio::test]
+async fn test_offline_mode_uses_stale_caches_and_lists_what_is_missing() {
+    // Every cached page is stale, so only offline mode keeps them from being revalidated.
+    std::env::set_var("WOVEN_METADATA_TTL", "0");
+    let cache_dir = tempfile::tempdir().unwrap();
+    std::env::set_var(CACHE_DIR_ENV, cache_dir.path());
+    let hits = Arc::new(AtomicUsize::new(0));
+    let root = serve(
+        HashMap::from([
+            ("/simple/demo/".to_string(), project_page("demo")),
+            (
+                "/files/demo-1.0-py3-none-any.whl.metadata".to_string(),
+                METADATA.to_string(),
+            ),
+            ("/simple/partial/".to_string(), project_page("partial")),
+        ]),
+        hits.clone(),
+    )
+    .await;
+    let resolve = |names: &[&str]| {
+        let source = IndexSource::new(Indexes::new(Index::new(&root)), "3.11");
+        let requirements: HashMap<String, String> =
+            names.iter().map(|name| (name.to_string(), String::new())).collect();
+        async move { resolve_with(&source, &requirements, &ResolveOptions::new("3.11")).await }
+    };
+
+    let online = resolve(&["demo"]).await.unwrap();
+    // Only the page of `partial` is cached, not the metadata of its release.
+    Indexes::new(Index::new(&root)).releases("partial").await.unwrap();
+    let requests = hits.load(Ordering::SeqCst);
+    assert!(requests > 0);
+
+    offline::enable();
+    let graph = resolve(&["demo"]).await.unwrap();
+    assert_eq!(graph.packages["demo"].version, online.packages["demo"].version);
+    assert_eq!(hits.load(Ordering::SeqCst), requests, "offline mode made a request");
+
+    // Resolution goes on past the first miss and lists all of them.
+    let host = root.trim_end_matches("/simple/");
+    let Err(WovenError::Offline(missing)) = resolve(&["absent", "demo", "other-absent", "partial"]).await else {
+        panic!("expected an offline error");
+    };
+    assert_eq!(
+        missing,
+        [
+            format!("{host}/files/partial-1.0-py3-none-any.whl.metadata (not in the metadata cache)"),
+            format!("{root}absent/ (not in the metadata cache)"),
+            format!("{root}other-absent/ (not in the metadata cache)"),
+        ]
+    );
+
+    // Installing lists every artifact that is not cached, without downloading any.
+    let mut lockfile = Lockfile::new("app", "0.1.0", "3.11");
+    for name in ["demo", "other"] {
+        let filename = format!("{name}-1.0-py3-none-any.whl");
+        lockfile.packages.insert(
+            name.to_string(),
+            LockedPackage {
+                version: "1.0".into(),
+                artifacts: vec![Artifact {
+                    url: format!("{root}../files/{filename}"),
+                    filename,
+                    sha256: "0".repeat(64),
+                    platform: "any".into(),
+                }],
+                ..Default::default()
+            },
+        );
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let result = installer::install_from_lock(
+        &lockfile,
+        &Selection::default(),
+        &HashSet::new(),
+        &Cache::new(dir.path().join("cache")),
+        dir.path(),
+        &dir.path().join("site-packages"),
+        &dir.path().join("bin"),
+        Arc::new(Quiet),
+    )
+    .await;
+    let Err(error @ WovenError::Offline(_)) = result else {
+        panic!("expected an offline error");
+    };
+    assert_eq!(
+        error.to_string(),
+        "Offline, and not available locally:\n  - demo 1.0 (demo-1.0-py3-none-any.whl)\n  - other 1.0 (other-1.0-py3-none-any.whl)"
+    );
+    assert_eq!(hits.load(Ordering::SeqCst), requests);
+}
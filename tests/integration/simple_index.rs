@@ -0,0 +1,520 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Once};
+
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use wovensnake::core::cache::CACHE_DIR_ENV;
+use wovensnake::core::config::{Config, IndexConfig, PackageSourceConfig};
+use wovensnake::core::resolver::{resolve_with, IndexSource, ResolveOptions};
+use wovensnake::core::timestamp::Timestamp;
+use wovensnake::dependencies::index::{Index, Indexes};
+
+const DEMO_METADATA: &str = "Metadata-Version: 2.1\nName: Demo\nVersion: 1.0\nRequires-Dist: idna>=3\n";
+const IDNA_METADATA: &str = "Metadata-Version: 2.1\nName: idna\nVersion: 3.7\n";
+
+const HTML: &str = "text/html";
+const JSON: &str = "application/vnd.pypi.simple.v1+json";
+
+/// Points every cache at a directory of this test run's own, so responses
+/// cached by an earlier run (on a port that happens to be reused) never
+/// answer for this one and the developer's cache is left alone.
+fn isolate_cache() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let dir = tempfile::tempdir().unwrap().keep();
+        std::env::set_var(CACHE_DIR_ENV, dir);
+    });
+}
+
+/// Serves `routes` (path → content type and body) over plain HTTP on a random
+/// local port and returns the simple API root, standing in for a private
+/// index. JSON pages are only served to clients that ask for them.
+async fn serve(routes: HashMap<String, (&'static str, String)>) -> String {
+    serve_with_auth(routes, None).await
+}
+
+/// Like [`serve`], answering `401` to requests without `Authorization: Basic <basic>`.
+async fn serve_with_auth(routes: HashMap<String, (&'static str, String)>, basic: Option<&'static str>) -> String {
+    isolate_cache();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let routes = Arc::new(routes);
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            let routes = routes.clone();
+            tokio::spawn(async move {
+                let mut buf = vec![0; 8192];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let accepts_json = request
+                    .lines()
+                    .any(|line| line.to_ascii_lowercase().starts_with("accept:") && line.contains(JSON));
+                let authorized = basic.is_none_or(|basic| {
+                    request
+                        .lines()
+                        .any(|line| line.eq_ignore_ascii_case(&format!("authorization: Basic {basic}")))
+                });
+                let response = match routes.get(path) {
+                    Some(_) if !authorized => {
+                        "HTTP/1.1 401 Unauthorized\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_string()
+                    }
+                    Some((content_type, body)) if *content_type != JSON || accepts_json => format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: {content_type}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    ),
+                    Some(_) => "HTTP/1.1 406 Not Acceptable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_string(),
+                    None => "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_string(),
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    format!("http://{addr}/simple/")
+}
+
+/// Answers every request with `status`, standing in for an index that is down.
+async fn serve_status(status: &'static str) -> String {
+    isolate_cache();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = vec![0; 8192];
+                let _ = stream.read(&mut buf).await;
+                let response = format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    format!("http://{addr}/simple/")
+}
+
+fn sha256(body: &str) -> String {
+    format!("{:x}", Sha256::digest(body.as_bytes()))
+}
+
+/// `demo` is listed as PEP 503 HTML and `idna` as PEP 691 JSON.
+async fn demo_index() -> String {
+    serve(HashMap::from([
+        (
+            "/simple/demo/".to_string(),
+            (
+                HTML,
+                format!(
+                    r#"<html><body>
+<a href="/files/demo-0.9.tar.gz#sha256=00ff">demo-0.9.tar.gz</a>
+<a href="/files/Demo-1.0-py3-none-any.whl#sha256=abcd" data-requires-python="&gt;=3.8" data-core-metadata="sha256={}">Demo-1.0-py3-none-any.whl</a>
+</body></html>"#,
+                    sha256(DEMO_METADATA)
+                ),
+            ),
+        ),
+        (
+            "/files/Demo-1.0-py3-none-any.whl.metadata".to_string(),
+            ("text/plain", DEMO_METADATA.to_string()),
+        ),
+        (
+            "/simple/idna/".to_string(),
+            (
+                JSON,
+                format!(
+                    r#"{{"meta": {{"api-version": "1.1"}}, "name": "idna", "files": [
+  {{"filename": "idna-3.6-py3-none-any.whl", "url": "../../files/idna-3.6-py3-none-any.whl",
+    "hashes": {{"sha256": "36"}}, "core-metadata": true, "upload-time": "2023-11-25T15:40:54Z"}},
+  {{"filename": "idna-3.7-py3-none-any.whl", "url": "../../files/idna-3.7-py3-none-any.whl",
+    "hashes": {{"sha256": "37"}}, "core-metadata": {{"sha256": "{}"}}, "upload-time": "2024-04-11T03:34:21Z"}}
+]}}"#,
+                    sha256(IDNA_METADATA)
+                ),
+            ),
+        ),
+        (
+            "/files/idna-3.6-py3-none-any.whl.metadata".to_string(),
+            ("text/plain", IDNA_METADATA.replace("3.7", "3.6")),
+        ),
+        (
+            "/files/idna-3.7-py3-none-any.whl.metadata".to_string(),
+            ("text/plain", IDNA_METADATA.to_string()),
+        ),
+    ]))
+    .await
+}
+
+#[tokio::test]
+async fn test_simple_index_lists_releases_and_metadata() {
+    let index = Index::new(&demo_index().await);
+
+    let releases = index.releases("DEMO").await.unwrap();
+    let mut versions: Vec<_> = releases.keys().cloned().collect();
+    versions.sort();
+    assert_eq!(versions, ["0.9", "1.0"]);
+    let wheel = &releases["1.0"][0];
+    assert_eq!(wheel.packagetype, "bdist_wheel");
+    assert_eq!(wheel.digests.sha256, "abcd");
+    assert_eq!(wheel.requires_python.as_deref(), Some(">=3.8"));
+
+    let info = index.release_metadata("demo", "1.0").await.unwrap();
+    assert_eq!(info.version, "1.0");
+    assert_eq!(info.requires_dist, Some(vec!["idna>=3".to_string()]));
+
+    let missing = index.releases("absent").await.unwrap_err().to_string();
+    assert!(missing.contains("absent"), "{missing}");
+}
+
+#[tokio::test]
+async fn test_metadata_files_are_only_read_when_advertised() {
+    let wheel = "quiet-1.0-py3-none-any.whl";
+    let root = serve(HashMap::from([
+        (
+            "/simple/quiet/".to_string(),
+            (HTML, format!(r#"<a href="/files/{wheel}#sha256=abcd">{wheel}</a>"#)),
+        ),
+        (
+            format!("/files/{wheel}.metadata"),
+            (
+                "text/plain",
+                "Metadata-Version: 2.1\nName: quiet\nVersion: 1.0\n".to_string(),
+            ),
+        ),
+    ]))
+    .await;
+
+    // The link has no `data-core-metadata`, so the wheel itself is read.
+    let error = Index::new(&root)
+        .release_metadata("quiet", "1.0")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("range request"), "{error}");
+}
+
+#[tokio::test]
+async fn test_resolves_against_simple_index() {
+    let index = Index::new(&demo_index().await);
+    let graph = resolve_with(
+        &IndexSource::new(Indexes::new(index), "3.11"),
+        &HashMap::from([("demo".to_string(), ">=1".to_string())]),
+        &ResolveOptions::new("3.11"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(graph.packages["demo"].version, "1.0");
+    assert_eq!(graph.packages["idna"].version, "3.7");
+    assert!(graph.packages["idna"].urls[0]
+        .url
+        .ends_with("/files/idna-3.7-py3-none-any.whl"));
+}
+
+#[tokio::test]
+async fn test_json_index_reports_upload_times() {
+    let index = Index::new(&demo_index().await);
+
+    let releases = index.releases("idna").await.unwrap();
+    let wheel = &releases["3.7"][0];
+    assert_eq!(wheel.digests.sha256, "37");
+    assert_eq!(wheel.upload_time_iso_8601.as_deref(), Some("2024-04-11T03:34:21Z"));
+    assert!(wheel.url.ends_with("/files/idna-3.7-py3-none-any.whl"));
+    assert_eq!(index.release_metadata("idna", "3.7").await.unwrap().version, "3.7");
+
+    let options = ResolveOptions {
+        exclude_newer: Some(Timestamp::from_str("2024-01-01T00:00:00Z").unwrap()),
+        ..ResolveOptions::new("3.11")
+    };
+    let graph = resolve_with(
+        &IndexSource::new(Indexes::new(index), "3.11"),
+        &HashMap::from([("demo".to_string(), String::new())]),
+        &options,
+    )
+    .await
+    .unwrap();
+    assert_eq!(graph.packages["idna"].version, "3.6");
+}
+
+/// A project page listing one wheel per version, each with metadata requiring `requires`.
+fn project(name: &str, versions: &[&str], requires: &str) -> Vec<(String, (&'static str, String))> {
+    let mut routes = Vec::new();
+    let mut page = String::new();
+    for version in versions {
+        let wheel = format!("{name}-{version}-py3-none-any.whl");
+        page.push_str(&format!(
+            r#"<a href="/files/{wheel}" data-core-metadata="true">{wheel}</a>"#
+        ));
+        routes.push((
+            format!("/files/{wheel}.metadata"),
+            (
+                "text/plain",
+                format!("Metadata-Version: 2.1\nName: {name}\nVersion: {version}\n{requires}"),
+            ),
+        ));
+    }
+    routes.push((format!("/simple/{name}/"), (HTML, page)));
+    routes
+}
+
+#[tokio::test]
+async fn test_first_matching_index_wins_and_pins_are_honoured() {
+    let private = serve(
+        [
+            project("internal-utils", &["1.0"], "Requires-Dist: idna\n"),
+            project("idna", &["3.6"], ""),
+        ]
+        .concat()
+        .into_iter()
+        .collect(),
+    )
+    .await;
+    let public = serve(
+        [
+            project("internal-utils", &["99.0"], ""),
+            project("idna", &["3.6", "3.7"], ""),
+            project("demo", &["1.0"], "Requires-Dist: internal-utils\n"),
+        ]
+        .concat()
+        .into_iter()
+        .collect(),
+    )
+    .await;
+
+    let config = |explicit: bool| Config {
+        python_version: "3.11".into(),
+        index_url: Some(public.clone()),
+        indexes: vec![IndexConfig {
+            name: "internal".into(),
+            url: private.clone(),
+            explicit,
+        }],
+        sources: HashMap::from([(
+            "Internal_Utils".to_string(),
+            PackageSourceConfig {
+                index: "internal".into(),
+            },
+        )]),
+        ..Default::default()
+    };
+    let resolve = |config: Config| async move {
+        let options = ResolveOptions::from_config(&config).unwrap();
+        resolve_with(
+            &IndexSource::new(options.indexes.clone(), "3.11"),
+            &HashMap::from([("demo".to_string(), String::new())]),
+            &options,
+        )
+        .await
+        .unwrap()
+    };
+
+    // The private index is searched first and has idna, so the public 3.7 is never seen.
+    let graph = resolve(config(false)).await;
+    assert_eq!(graph.packages["internal-utils"].version, "1.0");
+    assert_eq!(graph.packages["idna"].version, "3.6");
+    assert_eq!(
+        graph.packages["idna"].index.as_deref(),
+        Some(private.trim_end_matches('/'))
+    );
+    assert_eq!(
+        graph.packages["demo"].index.as_deref(),
+        Some(public.trim_end_matches('/'))
+    );
+
+    // An explicit index only serves the packages pinned to it.
+    let graph = resolve(config(true)).await;
+    assert_eq!(graph.packages["internal-utils"].version, "1.0");
+    assert_eq!(graph.packages["idna"].version, "3.7");
+
+    let mut unknown = config(false);
+    unknown.sources.insert(
+        "demo".into(),
+        PackageSourceConfig {
+            index: "missing".into(),
+        },
+    );
+    assert!(ResolveOptions::from_config(&unknown).is_err());
+}
+
+#[tokio::test]
+async fn test_failing_index_is_an_error_not_a_miss() {
+    let private = serve_status("503 Service Unavailable").await;
+    let public = serve(project("internal-utils", &["99.0"], "").into_iter().collect()).await;
+    let config = Config {
+        python_version: "3.11".into(),
+        index_url: Some(public),
+        indexes: vec![IndexConfig {
+            name: "internal".into(),
+            url: private.clone(),
+            explicit: false,
+        }],
+        ..Default::default()
+    };
+    let options = ResolveOptions::from_config(&config).unwrap();
+    let result = resolve_with(
+        &IndexSource::new(options.indexes.clone(), "3.11"),
+        &HashMap::from([("internal-utils".to_string(), String::new())]),
+        &options,
+    )
+    .await;
+
+    // The public look-alike must not be picked up while the private index is down.
+    let Err(error) = result else {
+        panic!("resolved from the public index while the private one was down");
+    };
+    let error = error.to_string();
+    assert!(error.contains("503"), "{error}");
+    assert!(error.contains(private.trim_end_matches('/')), "{error}");
+}
+
+#[tokio::test]
+async fn test_index_credentials_are_sent_but_never_recorded() {
+    let routes = || -> HashMap<_, _> { [project("secret-pkg", &["1.0"], "")].concat().into_iter().collect() };
+
+    // Userinfo in the index URL.
+    let root = serve_with_auth(routes(), Some("Y2k6czNjcmV0")).await;
+    let with_userinfo = root.replace("http://", "http://ci:s3cret@");
+    let index = Index::new(&with_userinfo);
+    assert_eq!(index.url(), root.trim_end_matches('/'));
+    let graph = resolve_with(
+        &IndexSource::new(Indexes::new(index), "3.11"),
+        &HashMap::from([("secret-pkg".to_string(), String::new())]),
+        &ResolveOptions::new("3.11"),
+    )
+    .await
+    .unwrap();
+    let node = &graph.packages["secret-pkg"];
+    assert_eq!(node.version, "1.0");
+    assert_eq!(node.index.as_deref(), Some(root.trim_end_matches('/')));
+    assert!(node.urls.iter().all(|u| !u.url.contains("s3cret")));
+
+    // Per-index environment variables.
+    let root = serve_with_auth(routes(), Some("ZW52LXVzZXI6ZW52LXBhc3M=")).await;
+    let config = Config {
+        python_version: "3.11".into(),
+        index_url: Some(root.clone()),
+        indexes: vec![IndexConfig {
+            name: "corp-private".into(),
+            url: root.clone(),
+            explicit: false,
+        }],
+        ..Default::default()
+    };
+    let unauthorized = Indexes::from_config(&config).unwrap().releases("secret-pkg").await;
+    assert!(unauthorized.unwrap_err().to_string().contains("401"));
+
+    std::env::set_var("WOVEN_INDEX_CORP_PRIVATE_USERNAME", "env-user");
+    std::env::set_var("WOVEN_INDEX_CORP_PRIVATE_PASSWORD", "env-pass");
+    let releases = Indexes::from_config(&config)
+        .unwrap()
+        .releases("secret-pkg")
+        .await
+        .unwrap();
+    assert!(releases.contains_key("1.0"));
+}
+
+/// Writes a wheel of `name` `version` whose only content is its metadata.
+fn write_wheel(dir: &std::path::Path, name: &str, version: &str, requires: &str) -> Vec<u8> {
+    use std::io::Write;
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        format!("{name}-{version}.dist-info/METADATA"),
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    write!(
+        zip,
+        "Metadata-Version: 2.1\nName: {name}\nVersion: {version}\n{requires}"
+    )
+    .unwrap();
+    let data = zip.finish().unwrap().into_inner();
+    std::fs::write(dir.join(format!("{name}-{version}-py3-none-any.whl")), &data).unwrap();
+    data
+}
+
+#[tokio::test]
+async fn test_resolves_from_find_links_without_an_index() {
+    isolate_cache();
+    let wheelhouse = tempfile::tempdir().unwrap();
+    write_wheel(wheelhouse.path(), "demo", "1.0", "Requires-Dist: helper>=1\n");
+    write_wheel(wheelhouse.path(), "demo", "2.0", "Requires-Python: >=3.99\n");
+    let helper = write_wheel(wheelhouse.path(), "helper", "2.0", "");
+    std::fs::write(wheelhouse.path().join("notes.txt"), "not a distribution").unwrap();
+
+    // Nothing listens on the index, so every release has to come from the wheelhouse.
+    let config = Config {
+        python_version: "3.11".into(),
+        index_url: Some("http://127.0.0.1:9/simple".into()),
+        find_links: vec![wheelhouse.path().display().to_string()],
+        ..Default::default()
+    };
+    let graph = resolve_with(
+        &IndexSource::new(Indexes::from_config(&config).unwrap(), "3.11"),
+        &HashMap::from([("demo".to_string(), String::new())]),
+        &ResolveOptions::new("3.11"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(graph.packages["demo"].version, "1.0");
+    let node = &graph.packages["helper"];
+    assert_eq!(node.version, "2.0");
+    assert_eq!(node.index, None);
+    assert!(node.urls[0].url.starts_with("file://"));
+    assert_eq!(node.urls[0].digests.sha256, format!("{:x}", Sha256::digest(&helper)));
+
+    // A flat HTML page listing the same files.
+    let page = wheelhouse.path().join("links.html");
+    std::fs::write(
+        &page,
+        r#"<a href="helper-2.0-py3-none-any.whl">helper-2.0-py3-none-any.whl</a>"#,
+    )
+    .unwrap();
+    let mut indexes = Indexes::new(Index::new("http://127.0.0.1:9/simple"));
+    indexes.find_links = vec![page.display().to_string()];
+    let files = indexes.release_files("helper", "2.0").await.unwrap();
+    assert_eq!(files[0].url, node.urls[0].url);
+    assert_eq!(files[0].digests.sha256, node.urls[0].digests.sha256);
+}
+
+#[tokio::test]
+async fn test_locks_hashes_of_remote_find_links_without_them() {
+    use wovensnake::core::cache::Cache;
+    use wovensnake::core::installer::{self, InstallReporter};
+
+    let wheel = "plain-1.0-py3-none-any.whl";
+    let body = "wheel bytes";
+    let root = serve(HashMap::from([
+        (
+            "/links/".to_string(),
+            (
+                HTML,
+                format!(r#"<a href="/files/{wheel}" data-core-metadata="true">{wheel}</a>"#),
+            ),
+        ),
+        (
+            format!("/files/{wheel}.metadata"),
+            (HTML, "Metadata-Version: 2.1\nName: plain\nVersion: 1.0\n".to_string()),
+        ),
+        (format!("/files/{wheel}"), (HTML, body.to_string())),
+    ]))
+    .await;
+
+    let config = Config {
+        name: "app".into(),
+        version: "0.1.0".into(),
+        python_version: "3.11".into(),
+        dependencies: HashMap::from([("plain".to_string(), String::new())]),
+        index_url: Some("http://127.0.0.1:9/simple".into()),
+        find_links: vec![format!("{}links/", root.strip_suffix("simple/").unwrap())],
+        ..Default::default()
+    };
+    let reporter: Arc<dyn InstallReporter> = wovensnake::cli::progress::CliProgressReporter::new();
+    let lockfile = installer::resolve_lockfile(&config, &reporter).await.unwrap();
+
+    let artifact = &lockfile.packages["plain"].artifacts[0];
+    assert_eq!(artifact.sha256, sha256(body));
+    assert!(Cache::init().unwrap().contains(wheel, &artifact.sha256));
+}
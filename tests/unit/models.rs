@@ -2,7 +2,9 @@ use std::collections::HashMap;
 use wovensnake::core::config::Config;
 use wovensnake::core::lock::Artifact;
 use wovensnake::core::selection::select_artifact;
-use wovensnake::dependencies::package::{select_best_candidate, Digests, PackageUrl};
+use wovensnake::core::settings::Settings;
+use wovensnake::dependencies::index::Index;
+use wovensnake::dependencies::package::select_best_candidate;
 
 #[test]
 fn test_config_model() {
@@ -12,6 +14,7 @@ fn test_config_model() {
         python_version: "3.8".into(),
         virtual_environment: "env".into(),
         dependencies: HashMap::from([("pip".into(), "20.0".into())]),
+        ..Default::default()
     };
 
     // Pure logic assertions
@@ -118,6 +121,7 @@ fn make_releases(versions: &[&str]) -> HashMap<String, Vec<wovensnake::dependenc
                 digests: Digests {
                     sha256: "deadbeef".to_string(),
                 },
+                ..Default::default()
             };
             (v.to_string(), vec![url])
         })
@@ -173,3 +177,334 @@ fn test_pep440_prerelease_skipped_when_stable_exists() {
     // 2.0a1 satisfies >=1.0 but is pre-release; 1.0 is stable and should win.
     assert_eq!(select_best_candidate(&releases, &specs).as_deref(), Some("1.0"));
 }
+
+#[test]
+fn test_candidates_skip_releases_excluding_project_python() {
+    use std::str::FromStr;
+    use wovensnake::dependencies::package::{candidate_versions, CandidatePolicy, SkipReason};
+    let mut releases = make_releases(&["1.0", "2.0"]);
+    for file in releases.get_mut("2.0").unwrap() {
+        file.requires_python = Some(">=3.10".to_string());
+    }
+    let specs = pep508_rs::pep440_rs::VersionSpecifiers::from_str("").unwrap();
+    let policy = CandidatePolicy {
+        python_version: Some(pep508_rs::pep440_rs::Version::from_str("3.9").unwrap()),
+        ..Default::default()
+    };
+    let candidates = candidate_versions("pkg", &releases, &specs, &policy);
+    let versions: Vec<&str> = candidates.versions.iter().map(|(_, k)| k.as_str()).collect();
+    assert_eq!(versions, vec!["1.0"]);
+    assert_eq!(candidates.skipped.len(), 1);
+    assert_eq!(
+        candidates.skipped[0].1,
+        SkipReason::RequiresPython(">=3.10".to_string())
+    );
+    assert_eq!(
+        candidates.skipped[0].1.describe("3.9"),
+        "requires Python >=3.10 (project uses Python 3.9)"
+    );
+    assert_eq!(
+        SkipReason::Yanked(Some("broken build".to_string())).describe("3.9"),
+        "yanked (broken build)"
+    );
+    assert_eq!(select_best_candidate(&releases, &specs).as_deref(), Some("2.0"));
+}
+
+#[test]
+fn test_pep440_yanked_release_only_selected_when_pinned() {
+    use std::str::FromStr;
+    let mut releases = make_releases(&["1.0", "1.1"]);
+    for file in releases.get_mut("1.1").unwrap() {
+        file.yanked = true;
+        file.yanked_reason = Some("broken build".to_string());
+    }
+    let range = pep508_rs::pep440_rs::VersionSpecifiers::from_str(">=1.0").unwrap();
+    assert_eq!(select_best_candidate(&releases, &range).as_deref(), Some("1.0"));
+    let pinned = pep508_rs::pep440_rs::VersionSpecifiers::from_str("==1.1").unwrap();
+    assert_eq!(select_best_candidate(&releases, &pinned).as_deref(), Some("1.1"));
+}
+
+#[test]
+fn test_prerelease_modes() {
+    use std::str::FromStr;
+    use wovensnake::core::config::PrereleaseMode;
+    use wovensnake::dependencies::package::{select_best_candidate_with, CandidatePolicy};
+    let releases = make_releases(&["1.0", "2.0b1"]);
+    let any = pep508_rs::pep440_rs::VersionSpecifiers::from_str("").unwrap();
+    let explicit = pep508_rs::pep440_rs::VersionSpecifiers::from_str(">=2.0b1").unwrap();
+    let pick = |name: &str, specs, prerelease, listed: &[&str]| {
+        let policy = CandidatePolicy {
+            prerelease,
+            prerelease_packages: listed.iter().map(ToString::to_string).collect(),
+            ..Default::default()
+        };
+        select_best_candidate_with(name, &releases, specs, &policy)
+    };
+
+    assert_eq!(
+        pick("pkg", &any, PrereleaseMode::AllowAll, &[]).as_deref(),
+        Some("2.0b1")
+    );
+    assert_eq!(pick("pkg", &explicit, PrereleaseMode::Disallow, &[]), None);
+    assert_eq!(
+        pick("pkg", &explicit, PrereleaseMode::IfNecessary, &[]).as_deref(),
+        Some("2.0b1")
+    );
+    assert_eq!(
+        pick("pkg", &any, PrereleaseMode::AllowIfExplicit, &[]).as_deref(),
+        Some("1.0")
+    );
+    assert_eq!(
+        pick("pkg", &explicit, PrereleaseMode::AllowIfExplicit, &[]).as_deref(),
+        Some("2.0b1")
+    );
+    assert_eq!(
+        pick("My-Pkg", &any, PrereleaseMode::AllowListed, &["my_pkg"]).as_deref(),
+        Some("2.0b1")
+    );
+    assert_eq!(
+        pick("other", &any, PrereleaseMode::AllowListed, &["my_pkg"]).as_deref(),
+        Some("1.0")
+    );
+}
+
+#[test]
+fn test_config_prerelease_settings() {
+    use wovensnake::core::config::PrereleaseMode;
+    let json = r#"{
+        "name": "demo",
+        "version": "0.1.0",
+        "python_version": "3.12",
+        "dependencies": {},
+        "virtualEnvironment": ".venv",
+        "prerelease": "allow-listed",
+        "prereleasePackages": ["pydantic"],
+        "excludeNewer": "2026-01-01T00:00:00Z"
+    }"#;
+    let conf: Config = serde_json::from_str(json).unwrap();
+    assert_eq!(conf.prerelease, PrereleaseMode::AllowListed);
+    assert_eq!(conf.prerelease_packages, vec!["pydantic".to_string()]);
+    assert_eq!(conf.exclude_newer.unwrap().to_string(), "2026-01-01T00:00:00Z");
+
+    let defaults = Config::default();
+    let written = serde_json::to_string(&defaults).unwrap();
+    assert!(!written.contains("prerelease"));
+    assert!(!written.contains("excludeNewer"));
+
+    let invalid = json.replace("2026-01-01T00:00:00Z", "next tuesday");
+    assert!(serde_json::from_str::<Config>(&invalid).is_err());
+}
+
+#[test]
+fn test_config_merges_constraint_files_with_inline_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("constraints.txt");
+    std::fs::write(&path, "# org-wide caps\nurllib3<2\nidna>=3,<4  # keep in sync\n\n").unwrap();
+
+    let conf = Config {
+        constraints: HashMap::from([("idna".into(), "<3.7".into())]),
+        constraints_file: Some(path.to_string_lossy().into_owned()),
+        overrides: HashMap::from([("pydantic".into(), ">=2".into())]),
+        ..Default::default()
+    };
+    let constraints = conf.constraints().unwrap();
+    assert_eq!(constraints["urllib3"], "<2");
+    assert_eq!(constraints["idna"], "<3.7");
+    assert_eq!(conf.overrides().unwrap()["pydantic"], ">=2");
+
+    std::fs::write(&path, "zope.interface<6\n").unwrap();
+    let spelled = Config {
+        constraints: HashMap::from([("Zope_Interface".into(), "<5".into())]),
+        constraints_file: conf.constraints_file.clone(),
+        ..Default::default()
+    };
+    let constraints = spelled.constraints().unwrap();
+    assert_eq!(constraints.len(), 1);
+    assert_eq!(constraints["Zope_Interface"], "<5");
+
+    std::fs::write(&path, "urllib3 @ https://example.com/urllib3.whl\n").unwrap();
+    assert!(conf.constraints().is_err());
+
+    std::fs::write(&path, "urllib3<2; python_version < \"3.8\"\n").unwrap();
+    let error = conf.constraints().unwrap_err().to_string();
+    assert!(error.contains("environment marker"), "{error}");
+
+    let missing = Config {
+        overrides_file: Some(dir.path().join("missing.txt").to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    assert!(missing.overrides().is_err());
+}
+
+#[test]
+fn test_config_direct_url_dependencies() {
+    use wovensnake::core::config::direct_url;
+    let wheel = "https://host.example/demo-1.0-py3-none-any.whl";
+    assert_eq!(direct_url(wheel), Some(wheel));
+    assert_eq!(direct_url(&format!("@ {wheel}")), Some(wheel));
+    assert_eq!(direct_url(">=2.31"), None);
+    assert_eq!(direct_url("2.31.0"), None);
+}
+
+#[test]
+fn test_config_local_path_dependencies() {
+    use wovensnake::core::config::{direct_url, local_path, LocalPath};
+    assert_eq!(
+        local_path("../libs/foo"),
+        Some(LocalPath {
+            path: "../libs/foo",
+            editable: false
+        })
+    );
+    assert_eq!(
+        local_path("-e ./packages/bar"),
+        Some(LocalPath {
+            path: "./packages/bar",
+            editable: true
+        })
+    );
+    assert!(local_path("/srv/libs/foo").is_some());
+    assert_eq!(local_path(">=1.0"), None);
+    assert_eq!(local_path("https://host.example/demo-1.0-py3-none-any.whl"), None);
+    assert_eq!(direct_url("../libs/foo"), None);
+}
+
+#[test]
+fn test_config_editable_packages_in_groups_and_extras() {
+    let config: Config = serde_json::from_str(
+        r#"{
+            "name": "demo",
+            "version": "0.1.0",
+            "python_version": "3.12",
+            "virtualEnvironment": ".venv",
+            "dependencies": { "core-lib": "../libs/core", "flask": ">=3" },
+            "dependencyGroups": { "dev": { "Dev_Tools": "-e ../libs/dev-tools" } },
+            "optionalDependencies": { "plugins": { "plugin": "-e ./plugin" } }
+        }"#,
+    )
+    .unwrap();
+    let mut editable: Vec<String> = config.editable_packages().iter().map(ToString::to_string).collect();
+    editable.sort();
+    assert_eq!(editable, ["dev-tools", "plugin"]);
+}
+
+#[test]
+fn test_config_dependency_groups() {
+    let config: Config = serde_json::from_str(
+        r#"{
+            "name": "demo",
+            "version": "0.1.0",
+            "python_version": "3.12",
+            "virtualEnvironment": ".venv",
+            "dependencies": { "flask": ">=3" },
+            "dependencyGroups": { "dev": { "pytest": ">=8", "ruff": "" } }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.dependency_groups["dev"]["pytest"], ">=8");
+    assert!(!config.dependencies.contains_key("pytest"));
+
+    let without_groups = serde_json::to_string(&Config::default()).unwrap();
+    assert!(!without_groups.contains("dependencyGroups"));
+}
+
+#[test]
+fn test_config_optional_dependencies() {
+    let config: Config = serde_json::from_str(
+        r#"{
+            "name": "demo",
+            "version": "0.1.0",
+            "python_version": "3.12",
+            "virtualEnvironment": ".venv",
+            "dependencies": { "sqlalchemy": ">=2" },
+            "optionalDependencies": { "postgres": { "psycopg": ">=3" }, "mysql": { "pymysql": "" } }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.optional_dependencies["postgres"]["psycopg"], ">=3");
+    assert_eq!(config.optional_dependencies.len(), 2);
+
+    let without_extras = serde_json::to_string(&Config::default()).unwrap();
+    assert!(!without_extras.contains("optionalDependencies"));
+}
+
+#[test]
+fn test_config_index_url_and_user_settings() {
+    let config: Config = serde_json::from_str(
+        r#"{
+            "name": "demo",
+            "version": "0.1.0",
+            "python_version": "3.12",
+            "virtualEnvironment": ".venv",
+            "dependencies": {},
+            "indexUrl": "https://devpi.example.com/root/pypi/+simple/"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.index_url.as_deref(),
+        Some("https://devpi.example.com/root/pypi/+simple/")
+    );
+    assert_eq!(
+        Index::new(config.index_url.as_deref().unwrap()).url(),
+        "https://devpi.example.com/root/pypi/+simple"
+    );
+    assert!(!serde_json::to_string(&Config::default()).unwrap().contains("indexUrl"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    assert_eq!(Settings::read(&path).unwrap(), Settings::default());
+    std::fs::write(&path, r#"{ "indexUrl": "https://mirror.example.com/simple" }"#).unwrap();
+    assert_eq!(
+        Settings::read(&path).unwrap().index_url.as_deref(),
+        Some("https://mirror.example.com/simple")
+    );
+    std::fs::write(&path, "{").unwrap();
+    assert!(Settings::read(&path).is_err());
+}
+
+#[test]
+fn test_config_named_indexes_and_sources() {
+    let config: Config = serde_json::from_str(
+        r#"{
+            "name": "demo",
+            "version": "0.1.0",
+            "python_version": "3.12",
+            "virtualEnvironment": ".venv",
+            "dependencies": { "internal-utils": ">=1" },
+            "indexes": [
+                { "name": "internal", "url": "https://pkgs.example.com/simple", "explicit": true },
+                { "name": "mirror", "url": "https://mirror.example.com/simple" }
+            ],
+            "sources": { "internal-utils": { "index": "internal" } }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.indexes.len(), 2);
+    assert!(config.indexes[0].explicit);
+    assert!(!config.indexes[1].explicit);
+    assert_eq!(config.sources["internal-utils"].index, "internal");
+
+    let json = serde_json::to_string(&config).unwrap();
+    assert!(!json.contains("\"explicit\":false"));
+    let defaults = serde_json::to_string(&Config::default()).unwrap();
+    assert!(!defaults.contains("indexes") && !defaults.contains("sources"));
+}
+
+#[test]
+fn test_config_find_links() {
+    let config: Config = serde_json::from_str(
+        r#"{
+            "name": "demo",
+            "version": "0.1.0",
+            "python_version": "3.12",
+            "virtualEnvironment": ".venv",
+            "dependencies": {},
+            "findLinks": ["./wheels", "https://example.com/wheels.html"]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.find_links, ["./wheels", "https://example.com/wheels.html"]);
+    assert!(serde_json::to_string(&config).unwrap().contains("\"findLinks\""));
+    assert!(!serde_json::to_string(&Config::default()).unwrap().contains("findLinks"));
+}
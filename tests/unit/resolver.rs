@@ -0,0 +1,759 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use wovensnake::core::config::{PrereleaseMode, ResolutionStrategy};
+use wovensnake::core::error::WovenError;
+use wovensnake::core::resolver::{resolve_with, PackageSource, Releases, ResolveOptions};
+use wovensnake::dependencies::package::{Digests, DirectArtifact, Info, PackageUrl, SkipReason};
+
+/// In-memory package index: name → version → `Requires-Dist` entries.
+#[derive(Default)]
+struct FakeIndex {
+    packages: HashMap<String, HashMap<String, Vec<String>>>,
+    requires_python: HashMap<(String, String), String>,
+    yanked: HashSet<(String, String)>,
+    uploaded: HashMap<(String, String), String>,
+    /// Direct URL → (name, version, `Requires-Dist`).
+    urls: HashMap<String, (String, String, Vec<String>)>,
+}
+
+impl FakeIndex {
+    fn with(mut self, name: &str, version: &str, requires: &[&str]) -> Self {
+        self.packages
+            .entry(name.to_string())
+            .or_default()
+            .insert(version.to_string(), requires.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    fn requiring_python(mut self, name: &str, version: &str, spec: &str) -> Self {
+        self.requires_python
+            .insert((name.to_string(), version.to_string()), spec.to_string());
+        self
+    }
+
+    fn uploaded_at(mut self, name: &str, version: &str, time: &str) -> Self {
+        self.uploaded
+            .insert((name.to_string(), version.to_string()), time.to_string());
+        self
+    }
+
+    fn at_url(mut self, url: &str, name: &str, version: &str, requires: &[&str]) -> Self {
+        self.urls.insert(
+            url.to_string(),
+            (
+                name.to_string(),
+                version.to_string(),
+                requires.iter().map(ToString::to_string).collect(),
+            ),
+        );
+        self
+    }
+
+    fn yanking(mut self, name: &str, version: &str) -> Self {
+        self.yanked.insert((name.to_string(), version.to_string()));
+        self
+    }
+}
+
+impl PackageSource for FakeIndex {
+    async fn releases(&self, name: &str) -> Result<Arc<Releases>, WovenError> {
+        let versions = self
+            .packages
+            .get(name)
+            .ok_or_else(|| format!("Could not find package {name} on PyPI"))?;
+        Ok(Arc::new(
+            versions
+                .keys()
+                .map(|v| {
+                    let file = PackageUrl {
+                        url: format!("https://files.example.com/{name}-{v}-py3-none-any.whl"),
+                        filename: format!("{name}-{v}-py3-none-any.whl"),
+                        packagetype: "bdist_wheel".to_string(),
+                        digests: Digests {
+                            sha256: "deadbeef".to_string(),
+                        },
+                        requires_python: self.requires_python.get(&(name.to_string(), v.clone())).cloned(),
+                        yanked: self.yanked.contains(&(name.to_string(), v.clone())),
+                        yanked_reason: None,
+                        upload_time_iso_8601: self.uploaded.get(&(name.to_string(), v.clone())).cloned(),
+                        ..Default::default()
+                    };
+                    (v.clone(), vec![file])
+                })
+                .collect(),
+        ))
+    }
+
+    async fn metadata(&self, name: &str, version: &str) -> Result<Arc<Info>, WovenError> {
+        let requires = &self.packages[name][version];
+        Ok(Arc::new(Info {
+            name: name.to_string(),
+            version: version.to_string(),
+            summary: None,
+            requires_dist: Some(requires.clone()),
+        }))
+    }
+
+    async fn direct(&self, url: &str) -> Result<Arc<DirectArtifact>, WovenError> {
+        let (name, version, requires) = self.urls.get(url).ok_or_else(|| format!("Could not download {url}"))?;
+        Ok(Arc::new(DirectArtifact {
+            info: Info {
+                name: name.clone(),
+                version: version.clone(),
+                summary: None,
+                requires_dist: Some(requires.clone()),
+            },
+            file: PackageUrl {
+                url: url.to_string(),
+                filename: url.rsplit('/').next().unwrap_or_default().to_string(),
+                packagetype: "bdist_wheel".to_string(),
+                digests: Digests {
+                    sha256: "cafef00d".to_string(),
+                },
+                ..Default::default()
+            },
+            commit: url
+                .starts_with("git+")
+                .then(|| "0123456789abcdef0123456789abcdef01234567".to_string()),
+        }))
+    }
+}
+
+fn roots(deps: &[(&str, &str)]) -> HashMap<String, String> {
+    deps.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()
+}
+
+fn picked(graph: &wovensnake::core::resolver::DependencyGraph, key: &str) -> String {
+    graph.packages[key].version.clone()
+}
+
+#[tokio::test]
+async fn test_resolver_picks_highest_compatible_versions() {
+    let index = FakeIndex::default()
+        .with("flask", "2.0.0", &["click>=7.0"])
+        .with("flask", "3.0.0", &["click>=8.1"])
+        .with("click", "7.1", &[])
+        .with("click", "8.1.7", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("flask", "")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "flask"), "3.0.0");
+    assert_eq!(picked(&graph, "click"), "8.1.7");
+    assert_eq!(graph.packages["flask"].dependencies, vec!["click".to_string()]);
+}
+
+#[tokio::test]
+async fn test_resolver_backtracks_to_downgrade_earlier_pick() {
+    // The newest boto3 needs a botocore that only works with urllib3 2.x, but the
+    // project caps urllib3 below 2 — the resolver must fall back to the older boto3.
+    let index = FakeIndex::default()
+        .with("boto3", "1.34.0", &["botocore>=1.34"])
+        .with("boto3", "1.28.0", &["botocore>=1.31,<1.32"])
+        .with("botocore", "1.34.0", &["urllib3>=2.0"])
+        .with("botocore", "1.31.0", &["urllib3<1.27"])
+        .with("urllib3", "1.26.18", &[])
+        .with("urllib3", "2.2.1", &[]);
+
+    let graph = resolve_with(
+        &index,
+        &roots(&[("boto3", ""), ("urllib3", "<2")]),
+        &ResolveOptions::new("3.12"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(picked(&graph, "boto3"), "1.28.0");
+    assert_eq!(picked(&graph, "botocore"), "1.31.0");
+    assert_eq!(picked(&graph, "urllib3"), "1.26.18");
+}
+
+#[tokio::test]
+async fn test_resolver_revisits_decision_blamed_for_conflict() {
+    // a==2 and b both constrain c, but incompatibly; only a==1 agrees with b.
+    let index = FakeIndex::default()
+        .with("a", "1.0", &["c==2.0"])
+        .with("a", "2.0", &["c==1.0"])
+        .with("b", "1.0", &["c==2.0"])
+        .with("c", "1.0", &[])
+        .with("c", "2.0", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("a", ""), ("b", "")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "a"), "1.0");
+    assert_eq!(picked(&graph, "b"), "1.0");
+    assert_eq!(picked(&graph, "c"), "2.0");
+}
+
+#[tokio::test]
+async fn test_resolver_reports_unsatisfiable_tree() {
+    // c is pinned below 2 by the project, but d needs c>=2 — no backtracking can help.
+    let index = FakeIndex::default()
+        .with("c", "1.0", &[])
+        .with("c", "2.0", &[])
+        .with("d", "1.0", &["c>=2"]);
+
+    let result = resolve_with(&index, &roots(&[("c", "<2"), ("d", "")]), &ResolveOptions::new("3.12")).await;
+    let Err(WovenError::NoSolution(report)) = result else {
+        panic!("expected a dependency conflict report");
+    };
+    assert_eq!(report.package, "c");
+    let conflict = report.conflicts.iter().find(|c| c.package == "d").unwrap();
+    assert_eq!(
+        conflict.rejected,
+        vec!["d 1.0 requires c>=2, but c 1.0 is already selected".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_conflict_report_traces_requirements_back_to_project() {
+    let index = FakeIndex::default()
+        .with("boto3", "1.34.0", &["botocore>=1.34"])
+        .with("botocore", "1.34.0", &["urllib3>=2.0"])
+        .with("urllib3", "1.26.18", &[])
+        .with("urllib3", "2.2.1", &[])
+        .with("legacy", "1.0", &["urllib3<2"]);
+
+    let result = resolve_with(
+        &index,
+        &roots(&[("boto3", ""), ("legacy", "")]),
+        &ResolveOptions::new("3.12"),
+    )
+    .await;
+    let Err(WovenError::NoSolution(report)) = result else {
+        panic!("expected a dependency conflict report");
+    };
+
+    let urllib3 = report.conflicts.iter().find(|c| c.package == "urllib3").unwrap();
+    let chains: Vec<String> = urllib3
+        .requirements
+        .iter()
+        .map(|d| format!("{} <- {}", d.requirement, d.chain.join(" -> ")))
+        .collect();
+    assert!(chains.contains(&"urllib3>=2.0 <- wovenpkg.json -> boto3==1.34.0 -> botocore==1.34.0".to_string()));
+    assert!(chains.contains(&"urllib3<2 <- wovenpkg.json -> legacy==1.0".to_string()));
+    assert!(report
+        .tried
+        .iter()
+        .any(|(name, versions)| name == "boto3" && versions == &["1.34.0"]));
+}
+
+#[tokio::test]
+async fn test_resolver_skips_requirements_excluded_by_markers() {
+    let index = FakeIndex::default()
+        .with("app", "1.0", &["legacy; python_version < '3.8'", "modern"])
+        .with("modern", "1.0", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("app", "1.0")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert!(graph.packages.contains_key("modern"));
+    assert!(!graph.packages.contains_key("legacy"));
+}
+
+#[tokio::test]
+async fn test_resolver_pulls_in_dependencies_of_requested_extras() {
+    let index = FakeIndex::default()
+        .with(
+            "requests",
+            "2.31.0",
+            &[
+                "urllib3>=1.21",
+                "PySocks>=1.5.6; extra == 'socks'",
+                "chardet; extra == 'charset'",
+            ],
+        )
+        .with("urllib3", "2.2.1", &[])
+        .with("pysocks", "1.7.1", &[]);
+
+    let graph = resolve_with(
+        &index,
+        &roots(&[("requests[socks]", ">=2.31")]),
+        &ResolveOptions::new("3.12"),
+    )
+    .await
+    .unwrap();
+    assert!(graph.packages.contains_key("pysocks"));
+    assert!(!graph.packages.contains_key("chardet"));
+    assert!(!graph.packages.keys().any(|k| k.contains('[')));
+
+    let requests = &graph.packages["requests"];
+    assert_eq!(requests.extras, vec!["socks".to_string()]);
+    assert!(requests.dependencies.contains(&"pysocks".to_string()));
+}
+
+#[tokio::test]
+async fn test_resolver_honours_extras_on_transitive_requirements() {
+    let index = FakeIndex::default()
+        .with("fastapi", "0.110.0", &["uvicorn[standard]>=0.12"])
+        .with("uvicorn", "0.29.0", &["h11>=0.8", "uvloop; extra == 'standard'"])
+        .with("h11", "0.14.0", &[])
+        .with("uvloop", "0.19.0", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("fastapi", "")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "uvloop"), "0.19.0");
+    assert_eq!(graph.packages["uvicorn"].extras, vec!["standard".to_string()]);
+}
+
+#[tokio::test]
+async fn test_resolver_skips_releases_excluding_project_python() {
+    let index = FakeIndex::default()
+        .with("numpy", "1.26.4", &[])
+        .with("numpy", "2.1.0", &[])
+        .requiring_python("numpy", "1.26.4", ">=3.9")
+        .requiring_python("numpy", "2.1.0", ">=3.10");
+
+    let graph = resolve_with(&index, &roots(&[("numpy", "")]), &ResolveOptions::new("3.9"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "numpy"), "1.26.4");
+    assert_eq!(graph.skipped.len(), 1);
+    assert_eq!(graph.skipped[0].version, "2.1.0");
+    assert_eq!(
+        graph.skipped[0].reason,
+        SkipReason::RequiresPython(">=3.10".to_string())
+    );
+
+    let graph = resolve_with(&index, &roots(&[("numpy", "")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "numpy"), "2.1.0");
+    assert!(graph.skipped.is_empty());
+}
+
+#[tokio::test]
+async fn test_resolver_avoids_yanked_releases_unless_pinned() {
+    let index = FakeIndex::default()
+        .with("attrs", "23.1.0", &[])
+        .with("attrs", "23.2.0", &[])
+        .yanking("attrs", "23.2.0");
+
+    let graph = resolve_with(&index, &roots(&[("attrs", ">=23")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "attrs"), "23.1.0");
+    assert!(!graph.packages["attrs"].yanked);
+    assert_eq!(graph.skipped[0].reason, SkipReason::Yanked(None));
+
+    let graph = resolve_with(&index, &roots(&[("attrs", "==23.2.0")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "attrs"), "23.2.0");
+    assert!(graph.packages["attrs"].yanked);
+}
+
+#[tokio::test]
+async fn test_resolver_applies_prerelease_policy_to_transitive_packages() {
+    let index = FakeIndex::default()
+        .with("app", "1.0", &["lib>=1"])
+        .with("lib", "1.5", &[])
+        .with("lib", "2.0rc1", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("app", "")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "lib"), "1.5");
+
+    let options = ResolveOptions {
+        prerelease: PrereleaseMode::AllowAll,
+        ..ResolveOptions::new("3.12")
+    };
+    let graph = resolve_with(&index, &roots(&[("app", "")]), &options).await.unwrap();
+    assert_eq!(picked(&graph, "lib"), "2.0rc1");
+
+    let options = ResolveOptions {
+        prerelease: PrereleaseMode::Disallow,
+        ..ResolveOptions::new("3.12")
+    };
+    let result = resolve_with(&index, &roots(&[("lib", ">=2.0rc1")]), &options).await;
+    assert!(matches!(result, Err(WovenError::NoSolution(_))));
+}
+
+#[tokio::test]
+async fn test_resolver_lowest_strategies() {
+    let index = FakeIndex::default()
+        .with("flask", "2.0.0", &["click>=7.0"])
+        .with("flask", "2.2.0", &["click>=8.0"])
+        .with("flask", "3.0.0", &["click>=8.1"])
+        .with("click", "7.1", &[])
+        .with("click", "8.0.0", &[])
+        .with("click", "8.1.7", &[]);
+    let with = |resolution| ResolveOptions {
+        resolution,
+        ..ResolveOptions::new("3.12")
+    };
+
+    let graph = resolve_with(&index, &roots(&[("flask", ">=2.2")]), &with(ResolutionStrategy::Lowest))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "flask"), "2.2.0");
+    assert_eq!(picked(&graph, "click"), "8.0.0");
+
+    let graph = resolve_with(
+        &index,
+        &roots(&[("flask", ">=2.2")]),
+        &with(ResolutionStrategy::LowestDirect),
+    )
+    .await
+    .unwrap();
+    assert_eq!(picked(&graph, "flask"), "2.2.0");
+    assert_eq!(picked(&graph, "click"), "8.1.7");
+}
+
+#[tokio::test]
+async fn test_resolver_ignores_files_uploaded_after_cutoff() {
+    let index = FakeIndex::default()
+        .with("httpx", "0.26.0", &[])
+        .with("httpx", "0.27.0", &[])
+        .uploaded_at("httpx", "0.26.0", "2023-12-20T14:37:21.123456Z")
+        .uploaded_at("httpx", "0.27.0", "2024-02-21T13:07:22.000000Z")
+        .with("Undated", "1.0", &[]);
+
+    let options = ResolveOptions {
+        exclude_newer: Some("2024-01-01T00:00:00Z".parse().unwrap()),
+        ..ResolveOptions::new("3.12")
+    };
+    let graph = resolve_with(&index, &roots(&[("httpx", "")]), &options).await.unwrap();
+    assert_eq!(picked(&graph, "httpx"), "0.26.0");
+    assert!(graph.skipped.is_empty());
+    assert!(graph.undated.is_empty());
+
+    // Without upload times the cutoff cannot apply, and the package is reported.
+    let graph = resolve_with(&index, &roots(&[("httpx", ""), ("Undated", "")]), &options)
+        .await
+        .unwrap();
+    assert_eq!(graph.undated, ["Undated"]);
+
+    let graph = resolve_with(&index, &roots(&[("httpx", "")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "httpx"), "0.27.0");
+}
+
+fn universal() -> ResolveOptions {
+    ResolveOptions {
+        universal: true,
+        ..ResolveOptions::new("3.12")
+    }
+}
+
+#[tokio::test]
+async fn test_universal_resolution_keeps_platform_markers() {
+    let index = FakeIndex::default()
+        .with(
+            "uvicorn",
+            "0.30.0",
+            &[
+                "uvloop>=0.15; sys_platform != 'win32'",
+                "colorama; python_version < '3.8'",
+            ],
+        )
+        .with("uvloop", "0.19.0", &[])
+        .with("colorama", "0.4.6", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("uvicorn", "")]), &universal())
+        .await
+        .unwrap();
+    assert_eq!(graph.packages["uvicorn"].marker, None);
+    assert_eq!(
+        graph.packages["uvloop"].marker.as_deref(),
+        Some("sys_platform != 'win32'")
+    );
+    // The project Python is fixed, so python_version markers are still decided up front.
+    assert!(!graph.packages.contains_key("colorama"));
+
+    let local = resolve_with(&index, &roots(&[("uvicorn", "")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(local.packages["uvicorn"].marker, None);
+}
+
+#[tokio::test]
+async fn test_universal_resolution_forks_on_disjoint_markers() {
+    let index = FakeIndex::default()
+        .with(
+            "app",
+            "1.0",
+            &["numpy>=2; sys_platform == 'linux'", "numpy<2; sys_platform == 'win32'"],
+        )
+        .with("numpy", "1.26.4", &[])
+        .with("numpy", "2.1.0", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("app", "")]), &universal())
+        .await
+        .unwrap();
+    assert_eq!(graph.packages["app"].marker, None);
+    assert_eq!(
+        graph.packages["numpy==2.1.0"].marker.as_deref(),
+        Some("sys_platform == 'linux'")
+    );
+    assert_eq!(
+        graph.packages["numpy==1.26.4"].marker.as_deref(),
+        Some("sys_platform == 'win32'")
+    );
+    assert!(!graph.packages.contains_key("numpy"));
+}
+
+#[tokio::test]
+async fn test_universal_resolution_forks_on_disjoint_markers_across_packages() {
+    let index = FakeIndex::default()
+        .with("client", "1.0", &["numpy>=2; sys_platform == 'linux'"])
+        .with("server", "1.0", &["numpy<2; sys_platform == 'win32'"])
+        .with("numpy", "1.26.4", &[])
+        .with("numpy", "2.1.0", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("client", ""), ("server", "")]), &universal())
+        .await
+        .unwrap();
+    assert_eq!(graph.packages["client"].marker, None);
+    assert_eq!(graph.packages["server"].marker, None);
+    assert_eq!(
+        graph.packages["numpy==2.1.0"].marker.as_deref(),
+        Some("sys_platform == 'linux'")
+    );
+    assert_eq!(
+        graph.packages["numpy==1.26.4"].marker.as_deref(),
+        Some("sys_platform == 'win32'")
+    );
+}
+
+#[tokio::test]
+async fn test_resolver_applies_constraints_and_overrides() {
+    let index = FakeIndex::default()
+        .with("requests", "2.31.0", &["urllib3>=1.21,<3"])
+        .with("urllib3", "1.26.18", &[])
+        .with("urllib3", "2.2.1", &[])
+        .with("legacy", "1.0", &["pydantic<2"])
+        .with("pydantic", "1.10.0", &[])
+        .with("pydantic", "2.7.0", &[])
+        .with("unused", "1.0", &[]);
+
+    let options = ResolveOptions {
+        constraints: roots(&[("urllib3", "<2"), ("unused", "<2")]),
+        overrides: roots(&[("pydantic", ">=2")]),
+        ..ResolveOptions::new("3.12")
+    };
+    let graph = resolve_with(&index, &roots(&[("requests", ""), ("legacy", "")]), &options)
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "urllib3"), "1.26.18");
+    assert_eq!(picked(&graph, "pydantic"), "2.7.0");
+    assert!(graph.packages["pydantic"].overridden);
+    assert!(!graph.packages["urllib3"].overridden);
+    // Constraints limit versions but never add packages.
+    assert!(!graph.packages.contains_key("unused"));
+}
+
+#[tokio::test]
+async fn test_override_applies_to_packages_decided_before_their_dependents() {
+    let index = FakeIndex::default()
+        .with("stale-client", "1.0", &["pydantic<2"])
+        .with("pydantic", "1.10.0", &[])
+        .with("pydantic", "2.7.0", &[]);
+
+    let options = ResolveOptions {
+        overrides: roots(&[("pydantic", ">=2")]),
+        ..ResolveOptions::new("3.12")
+    };
+    // `pydantic` is a root, so 2.7.0 is picked before `stale-client` asks for `<2`.
+    let graph = resolve_with(&index, &roots(&[("pydantic", ""), ("stale-client", "")]), &options)
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "pydantic"), "2.7.0");
+    assert_eq!(picked(&graph, "stale-client"), "1.0");
+}
+
+/// Wraps an index with slow release listings and records how many were in flight at once.
+struct SlowIndex {
+    inner: FakeIndex,
+    in_flight: std::sync::atomic::AtomicUsize,
+    peak: std::sync::atomic::AtomicUsize,
+}
+
+impl PackageSource for SlowIndex {
+    async fn releases(&self, name: &str) -> Result<Arc<Releases>, WovenError> {
+        use std::sync::atomic::Ordering;
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.inner.releases(name).await
+    }
+
+    async fn metadata(&self, name: &str, version: &str) -> Result<Arc<Info>, WovenError> {
+        self.inner.metadata(name, version).await
+    }
+
+    async fn direct(&self, url: &str) -> Result<Arc<DirectArtifact>, WovenError> {
+        self.inner.direct(url).await
+    }
+}
+
+#[tokio::test]
+async fn test_resolver_prefetches_queued_packages_concurrently() {
+    let index = SlowIndex {
+        inner: FakeIndex::default()
+            .with("app", "1.0", &["a", "b", "c", "d"])
+            .with("a", "1.0", &[])
+            .with("b", "1.0", &[])
+            .with("c", "1.0", &[])
+            .with("d", "1.0", &[]),
+        in_flight: 0.into(),
+        peak: 0.into(),
+    };
+
+    let graph = resolve_with(&index, &roots(&[("app", "")]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(graph.packages.len(), 5);
+    assert!(index.peak.load(std::sync::atomic::Ordering::SeqCst) >= 4);
+}
+
+#[tokio::test]
+async fn test_resolver_uses_direct_url_artifacts() {
+    let wheel = "https://files.example.com/internal/demo-1.4.0-py3-none-any.whl";
+    let index = FakeIndex::default()
+        .at_url(
+            wheel,
+            "demo",
+            "1.4.0",
+            &[
+                "click>=8",
+                "helper @ https://files.example.com/helper-0.1-py3-none-any.whl",
+            ],
+        )
+        .at_url(
+            "https://files.example.com/helper-0.1-py3-none-any.whl",
+            "helper",
+            "0.1",
+            &[],
+        )
+        .with("click", "8.1.7", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("demo", wheel)]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "demo"), "1.4.0");
+    assert_eq!(graph.packages["demo"].urls[0].url, wheel);
+    assert_eq!(graph.packages["demo"].urls[0].digests.sha256, "cafef00d");
+    assert_eq!(picked(&graph, "helper"), "0.1");
+    assert_eq!(picked(&graph, "click"), "8.1.7");
+
+    // A version requirement elsewhere in the tree must agree with the URL's version.
+    let clash = resolve_with(
+        &index.with("app", "1.0", &["demo>=2"]),
+        &roots(&[("demo", wheel), ("app", "")]),
+        &ResolveOptions::new("3.12"),
+    )
+    .await;
+    assert!(matches!(clash, Err(WovenError::NoSolution(_))));
+}
+
+#[tokio::test]
+async fn test_resolver_records_commit_of_git_requirements() {
+    let repo = "git+https://git.example.com/internal/tools.git@v2";
+    let index = FakeIndex::default()
+        .at_url(repo, "tools", "2.0.0", &["click>=8"])
+        .with("click", "8.1.7", &[]);
+
+    let graph = resolve_with(&index, &roots(&[("tools", repo)]), &ResolveOptions::new("3.12"))
+        .await
+        .unwrap();
+    assert_eq!(picked(&graph, "tools"), "2.0.0");
+    assert_eq!(
+        graph.packages["tools"].commit.as_deref(),
+        Some("0123456789abcdef0123456789abcdef01234567")
+    );
+    assert_eq!(graph.packages["click"].commit, None);
+}
+
+#[tokio::test]
+async fn test_resolver_reads_path_dependencies_locally() {
+    let index = FakeIndex::default()
+        .at_url("../libs/foo", "foo", "0.3.0", &["click>=8"])
+        .with("click", "8.1.7", &[]);
+
+    let graph = resolve_with(
+        &index,
+        &roots(&[("foo", "-e ../libs/foo")]),
+        &ResolveOptions::new("3.12"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(picked(&graph, "foo"), "0.3.0");
+    assert_eq!(graph.packages["foo"].path.as_deref(), Some("../libs/foo"));
+    assert_eq!(graph.packages["foo"].commit, None);
+    assert_eq!(graph.packages["click"].path, None);
+}
+
+#[tokio::test]
+async fn test_resolver_locks_dependency_groups_together() {
+    let index = FakeIndex::default()
+        .with("flask", "3.0.0", &["click>=8"])
+        .with("click", "8.0.0", &[])
+        .with("click", "8.1.7", &[])
+        .with("pytest", "8.0.0", &["pluggy"])
+        .with("pluggy", "1.5.0", &[])
+        .with("black", "24.1.0", &["click<8.1"]);
+
+    let mut options = ResolveOptions::new("3.12");
+    options.groups = HashMap::from([
+        ("dev".to_string(), roots(&[("pytest", ""), ("black", "")])),
+        ("docs".to_string(), roots(&[("click", "")])),
+    ]);
+    let graph = resolve_with(&index, &roots(&[("flask", "")]), &options).await.unwrap();
+
+    // `black` in the dev group holds click back for the production tree too.
+    assert_eq!(picked(&graph, "click"), "8.0.0");
+    assert_eq!(graph.packages["click"].groups, ["dev", "docs"]);
+    assert!(!graph.packages["click"].optional);
+    assert!(graph.packages["flask"].groups.is_empty());
+    assert_eq!(graph.packages["pluggy"].groups, ["dev"]);
+    assert!(graph.packages["pluggy"].optional);
+}
+
+#[tokio::test]
+async fn test_resolver_locks_every_project_extra() {
+    let index = FakeIndex::default()
+        .with("sqlalchemy", "2.0.0", &[])
+        .with("psycopg", "3.1.0", &["typing-extensions"])
+        .with("typing-extensions", "4.12.0", &[])
+        .with("pymysql", "1.1.0", &[]);
+
+    let mut options = ResolveOptions::new("3.12");
+    options.project_extras = HashMap::from([
+        ("postgres".to_string(), roots(&[("psycopg", "")])),
+        ("mysql".to_string(), roots(&[("pymysql", "")])),
+    ]);
+    let graph = resolve_with(&index, &roots(&[("sqlalchemy", "")]), &options)
+        .await
+        .unwrap();
+
+    assert!(!graph.packages["sqlalchemy"].optional);
+    assert!(graph.packages["sqlalchemy"].project_extras.is_empty());
+    assert_eq!(graph.packages["typing-extensions"].project_extras, ["postgres"]);
+    assert!(graph.packages["typing-extensions"].optional);
+    assert_eq!(graph.packages["pymysql"].project_extras, ["mysql"]);
+}
+
+#[tokio::test]
+async fn test_resolver_merges_spellings_of_one_package() {
+    let index = FakeIndex::default()
+        .with("Zope.Interface", "5.0.0", &[])
+        .with("Zope.Interface", "6.0.0", &[])
+        .with("twisted", "24.3.0", &["zope_interface<6"]);
+
+    let graph = resolve_with(
+        &index,
+        &roots(&[("Zope.Interface", ""), ("twisted", "")]),
+        &ResolveOptions::new("3.12"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(picked(&graph, "zope-interface"), "5.0.0");
+    assert_eq!(graph.packages.len(), 2);
+}